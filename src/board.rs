@@ -1,22 +1,54 @@
+use crate::chess_move::{Move, MoveError, MoveOutcome};
 use crate::color::Color;
+use crate::game_result::{DrawReason, GameResult};
 use crate::move_record::MoveRecord;
 use crate::piece::{Piece, PieceType};
 use crate::space::Space;
+use crate::zobrist;
+use bitboard::Bitboards;
 use std::array::from_fn;
 use std::collections::HashMap;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+pub mod bitboard;
+pub mod fen;
+pub mod perft;
+pub mod san;
+
+#[derive(Clone, Debug)]
 pub struct Board {
     spaces: [[Space; 8]; 8],
+    bitboards: Bitboards, // the same pieces as spaces, by color and type
     turn_color: Color,
     moves: Vec<MoveRecord>,
+    halfmove_clock: u16,
+    starting_fullmove: u16,
+    starting_en_passant: Option<(u8, u8)>,
+    position_hashes: Vec<u64>,
     captured_by_white: HashMap<PieceType, u8>,
     captured_by_black: HashMap<PieceType, u8>,
+    redo_moves: Vec<Move>, // undone moves, most recently undone last
+}
+
+// boards are compared by position and history, not by what could be redone
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.spaces == other.spaces
+            && self.turn_color == other.turn_color
+            && self.moves == other.moves
+            && self.halfmove_clock == other.halfmove_clock
+            && self.starting_fullmove == other.starting_fullmove
+            && self.starting_en_passant == other.starting_en_passant
+            && self.position_hashes == other.position_hashes
+            && self.captured_by_white == other.captured_by_white
+            && self.captured_by_black == other.captured_by_black
+    }
 }
 
+impl Eq for Board {}
+
 impl Board {
     pub fn new() -> Self {
-        Self {
+        let mut board = Self {
             spaces: from_fn(|row| {
                 from_fn(|col| {
                     let color = if (row + col) % 2 == 0 {
@@ -49,11 +81,19 @@ impl Board {
                     Space::new(color, piece)
                 })
             }),
+            bitboards: Bitboards::default(),
             turn_color: Color::White,
             moves: vec![],
+            halfmove_clock: 0,
+            starting_fullmove: 1,
+            starting_en_passant: None,
+            position_hashes: vec![],
             captured_by_white: HashMap::new(),
             captured_by_black: HashMap::new(),
-        }
+            redo_moves: vec![],
+        };
+        board.set_up();
+        board
     }
 
     pub fn make_custom(placements: Vec<(Piece, u8, u8)>, starting_color: Color) -> Self {
@@ -71,13 +111,21 @@ impl Board {
             let space_color = spaces[p.2 as usize][p.1 as usize].color();
             spaces[p.2 as usize][p.1 as usize] = Space::new(space_color, Some(p.0));
         }
-        Self {
+        let mut board = Self {
             spaces,
+            bitboards: Bitboards::default(),
             turn_color: starting_color,
             moves: vec![],
+            halfmove_clock: 0,
+            starting_fullmove: 1,
+            starting_en_passant: None,
+            position_hashes: vec![],
             captured_by_white: HashMap::new(),
             captured_by_black: HashMap::new(),
-        }
+            redo_moves: vec![],
+        };
+        board.set_up();
+        board
     }
 
     // TODO: this only sets piece placements and turn color, it doesnt set moves or mark pieces as having moved
@@ -124,13 +172,21 @@ impl Board {
             Some('B') => Color::Black,
             _ => panic!("Unrecognized character in board state color"),
         };
-        Self {
+        let mut board = Self {
             spaces,
+            bitboards: Bitboards::default(),
             turn_color,
             moves: vec![],
+            halfmove_clock: 0,
+            starting_fullmove: 1,
+            starting_en_passant: None,
+            position_hashes: vec![],
             captured_by_white: HashMap::new(),
             captured_by_black: HashMap::new(),
-        }
+            redo_moves: vec![],
+        };
+        board.set_up();
+        board
     }
 
     pub fn space(&self, x: u8, y: u8) -> &Space {
@@ -141,10 +197,152 @@ impl Board {
         &self.spaces
     }
 
+    pub fn bitboards(&self) -> &Bitboards {
+        &self.bitboards
+    }
+
+    // fills in what follows from the piece placement, for the constructors
+    fn set_up(&mut self) {
+        self.bitboards = Bitboards::from_spaces(&self.spaces);
+        self.record_position();
+    }
+
+    // every change to the placement goes through take_piece and put_piece to keep the bitboards in step
+    fn take_piece(&mut self, x: u8, y: u8) -> Option<Piece> {
+        let piece = self.spaces[y as usize][x as usize].remove_piece()?;
+        self.bitboards
+            .toggle(piece.color(), piece.piece_type(), x, y);
+        Some(piece)
+    }
+
+    fn put_piece(&mut self, x: u8, y: u8, piece: Piece) {
+        debug_assert!(self.space(x, y).piece().is_none());
+        self.bitboards
+            .toggle(piece.color(), piece.piece_type(), x, y);
+        self.spaces[y as usize][x as usize].set_piece(Some(piece));
+    }
+
     pub fn turn_color(&self) -> Color {
         self.turn_color
     }
 
+    pub fn fullmove_number(&self) -> u16 {
+        let started_black = (self.turn_color == Color::Black) == self.moves.len().is_multiple_of(2);
+        #[allow(clippy::cast_possible_truncation)]
+        let full_moves = usize::midpoint(self.moves.len(), usize::from(started_black)) as u16;
+        self.starting_fullmove + full_moves
+    }
+
+    pub fn halfmove_clock(&self) -> u16 {
+        self.halfmove_clock
+    }
+
+    // updates the halfmove clock, turn, and position history, must be called after the move has been recorded
+    fn finish_move(&mut self) {
+        let last_move = self.moves.last().unwrap();
+        // the halfmove clock resets on pawn moves and captures
+        if last_move.piece_type() == PieceType::Pawn || last_move.is_capture() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        // the bitboards mirror the spaces, so any drift between them is a bug in take_piece or put_piece
+        debug_assert_eq!(self.bitboards, Bitboards::from_spaces(&self.spaces));
+        self.toggle_turn();
+        self.record_position();
+    }
+
+    fn record_position(&mut self) {
+        let hash = self.position_hash();
+        self.position_hashes.push(hash);
+    }
+
+    pub fn position_hash(&self) -> u64 {
+        let mut hash = 0;
+        for color in [Color::White, Color::Black] {
+            for piece_type in [
+                PieceType::King,
+                PieceType::Queen,
+                PieceType::Rook,
+                PieceType::Bishop,
+                PieceType::Knight,
+                PieceType::Pawn,
+            ] {
+                for (x, y) in bitboard::spaces(self.bitboards.pieces(color, piece_type)) {
+                    hash ^= zobrist::piece_key(color, piece_type, x, y);
+                }
+            }
+        }
+        if self.turn_color == Color::Black {
+            hash ^= zobrist::black_to_move_key();
+        }
+        for color in [Color::White, Color::Black] {
+            for kingside in [true, false] {
+                if self.can_castle(color, kingside) {
+                    hash ^= zobrist::castling_key(color, kingside);
+                }
+            }
+        }
+        // en passant only makes positions differ if a pawn is actually in place to capture
+        if let Some((x, y)) = self.en_passant_target() {
+            let pawn_rank = match self.turn_color {
+                Color::White => y - 1,
+                Color::Black => y + 1,
+            };
+            let can_capture = [x.checked_sub(1), Some(x + 1)]
+                .into_iter()
+                .flatten()
+                .filter(|&pawn_x| pawn_x < 8)
+                .any(|pawn_x| {
+                    self.space(pawn_x, pawn_rank).piece().is_some_and(|p| {
+                        p.piece_type() == PieceType::Pawn && p.color() == self.turn_color
+                    })
+                });
+            if can_capture {
+                hash ^= zobrist::en_passant_key(x);
+            }
+        }
+        hash
+    }
+
+    // whether color's king and rook are both unmoved on their starting spaces for that side
+    pub fn can_castle(&self, color: Color, kingside: bool) -> bool {
+        let rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+        let rook_file = if kingside { 7 } else { 0 };
+        let unmoved = |x: u8, piece_type: PieceType| {
+            self.space(x, rank).piece().is_some_and(|p| {
+                p.piece_type() == piece_type && p.color() == color && !p.has_moved()
+            })
+        };
+        unmoved(4, PieceType::King) && unmoved(rook_file, PieceType::Rook)
+    }
+
+    // the space a pawn skipped over with a two space move on the last turn
+    pub fn en_passant_target(&self) -> Option<(u8, u8)> {
+        let Some(last_move) = self.moves.last() else {
+            return self.starting_en_passant;
+        };
+        let (x1, y1) = last_move.origin();
+        let (x2, y2) = last_move.dest();
+        if last_move.piece_type() == PieceType::Pawn && x1 == x2 && (y1 + 2 == y2 || y2 + 2 == y1) {
+            Some((x1, u8::midpoint(y1, y2)))
+        } else {
+            None
+        }
+    }
+
+    // how many times the current position has occurred, including now
+    pub fn repetition_count(&self) -> usize {
+        let current = self.position_hashes.last().unwrap();
+        self.position_hashes
+            .iter()
+            .filter(|&hash| hash == current)
+            .count()
+    }
+
     fn toggle_turn(&mut self) {
         self.turn_color = match self.turn_color {
             Color::White => Color::Black,
@@ -152,142 +350,138 @@ impl Board {
         };
     }
 
-    pub fn move_piece(&mut self, x1: u8, y1: u8, x2: u8, y2: u8) -> bool {
-        if x1 == x2 && y1 == y2 {
-            return false;
+    pub fn move_piece(&mut self, x1: u8, y1: u8, x2: u8, y2: u8) -> Result<MoveOutcome, MoveError> {
+        self.make_move(Move::new(x1, y1, x2, y2, None))
+    }
+
+    pub fn make_move(&mut self, mv: Move) -> Result<MoveOutcome, MoveError> {
+        let outcome = self.make(mv)?;
+        // replaying the next undone move keeps the rest of the redo stack, anything else diverges
+        if self.redo_moves.last() == Some(&mv) {
+            self.redo_moves.pop();
+        } else {
+            self.redo_moves.clear();
         }
-        let piece = self.space(x1, y1).piece();
-        if piece.is_none() {
-            return false;
+        Ok(outcome)
+    }
+
+    // makes mv if it is legal, leaving the redo stack alone, for searches that unmake it again
+    pub fn make(&mut self, mv: Move) -> Result<MoveOutcome, MoveError> {
+        let (x1, y1) = mv.origin();
+        let (x2, y2) = mv.dest();
+        if x1 == x2 && y1 == y2 {
+            return Err(MoveError::SameSpace);
         }
-        let piece = piece.as_ref().unwrap();
+        let Some(piece) = self.space(x1, y1).piece() else {
+            return Err(MoveError::NoPiece);
+        };
         let color = piece.color();
         if color != self.turn_color {
-            return false;
+            return Err(MoveError::WrongTurn);
         }
         let piece2 = self.space(x2, y2).piece();
 
         let is_promotion = piece.piece_type() == PieceType::Pawn
             && ((piece.color() == Color::White && y2 == 7)
                 || (piece.color() == Color::Black && y2 == 0));
+        if let Some(promotion) = mv.promotion()
+            && (!is_promotion || matches!(promotion, PieceType::Pawn | PieceType::King))
+        {
+            return Err(MoveError::InvalidPromotion);
+        }
 
         // Check and execute en passant here since piece removal from capture is different than normal
         if piece.piece_type() == PieceType::Pawn
-            && piece2.is_none()
-            && ((self.turn_color == Color::White && y1 == 4 && y2 == 5)
-                || (self.turn_color == Color::Black && y1 == 3 && y2 == 2))
+            && self.en_passant_target() == Some((x2, y2))
+            && (x1 + 1 == x2 || x2 + 1 == x1) // moving pawn is moving diagonally
+            && match color {
+                Color::White => y1 + 1 == y2,
+                Color::Black => y2 + 1 == y1,
+            }
         {
-            if let Some(last_move) = self.moves.last() {
-                // there was a previous move
-                if last_move.piece_type() == PieceType::Pawn // last move was a pawn
-                    && (x1 + 1 == x2 || x2 + 1 == x1) // moving pawn is moving diagonally (we already checked it's moving forward, checking left/right here)
-                    && last_move.origin().0 == last_move.dest().0 // the last move was directly forward
-                    && (last_move.origin().1 + 2 == last_move.dest().1 || last_move.dest().1 + 2 == last_move.origin().1) // the last move was across two ranks
-                    && x2 == last_move.dest().0
-                // the current move is moving into the last pawn's file
-                {
-                    let last_dest = last_move.dest();
-                    let mut piece = self.spaces[y1 as usize][x1 as usize]
-                        .remove_piece()
-                        .unwrap();
-                    let piece2 = self.spaces[last_dest.1 as usize][last_dest.0 as usize]
-                        .remove_piece()
-                        .unwrap(); //get and remove last_move pawn
-                    self.record_capture_by(piece.color(), piece2.piece_type());
-                    self.moves.push(MoveRecord::new(
-                        x1,
-                        y1,
-                        x2,
-                        y2,
-                        Some(piece2),
-                        piece.piece_type(),
-                        !piece.has_moved(),
-                        is_promotion,
-                    ));
-                    piece.mark_moved();
-                    self.spaces[y2 as usize][x2 as usize].set_piece(Some(piece));
-                    self.toggle_turn();
-
-                    // undo this move if it has put the player in check
-                    if self.is_in_check(color) {
-                        self.undo_last_move();
-                        return false;
-                    }
-                    return true;
-                }
+            let mut piece = self.take_piece(x1, y1).unwrap();
+            // the pawn that moved past the target is beside the origin
+            let piece2 = self.take_piece(x2, y1).unwrap();
+            self.record_capture_by(piece.color(), piece2.piece_type());
+            self.moves.push(MoveRecord::new(
+                x1,
+                y1,
+                x2,
+                y2,
+                Some(piece2),
+                piece.piece_type(),
+                !piece.has_moved(),
+                None,
+                true,
+                self.halfmove_clock,
+            ));
+            piece.mark_moved();
+            self.put_piece(x2, y2, piece);
+            self.finish_move();
+
+            // undo this move if it has put the player in check
+            if self.is_in_check(color) {
+                self.unmake();
+                return Err(MoveError::LeavesKingInCheck);
             }
+            return Ok(MoveOutcome::EnPassant);
         }
 
         // Check and execute castling here since piece movement is different than normal
-        if piece.piece_type() == PieceType::King
-            && !piece.has_moved()
-            && (x1 + 2 == x2 || x2 + 2 == x1)
-            && piece2.is_none()
-        {
-            let rank = match color {
-                Color::White => 0,
-                Color::Black => 7,
-            };
-            if y1 == rank && y2 == rank {
-                let rook = if x1 + 2 == x2 {
-                    self.space(7, rank).piece()
-                } else {
-                    //we already checked that either x1 + 2 == x2 or x2 + 2 == x1
-                    self.space(0, rank).piece()
-                };
-                if let Some(rook) = rook {
-                    if !rook.has_moved() && rook.color() == color {
-                        // king cannot move out of, through, or into check
-                        if self.is_space_attacked(x1, y1, color) {
-                            return false;
-                        }
-                        if x1 + 2 == x2 {
-                            if self.is_space_attacked(x1 + 1, y1, color)
-                                || self.is_space_attacked(x2, y1, color)
-                                || self.space(x1 + 1, y1).piece().is_some()
-                            {
-                                return false;
-                            }
-                        } else if self.is_space_attacked(x1 - 1, y1, color)
-                            || self.is_space_attacked(x2, y1, color)
-                            || self.space(x1 - 1, y1).piece().is_some()
-                        {
-                            return false;
-                        }
-                        let mut piece = self.spaces[y1 as usize][x1 as usize]
-                            .remove_piece()
-                            .unwrap();
-                        self.moves.push(MoveRecord::new(
-                            x1,
-                            y1,
-                            x2,
-                            y2,
-                            None,
-                            piece.piece_type(),
-                            !piece.has_moved(),
-                            is_promotion,
-                        ));
-                        piece.mark_moved();
-                        self.spaces[y2 as usize][x2 as usize].set_piece(Some(piece));
-                        if x1 + 2 == x2 {
-                            let mut rook = self.spaces[y1 as usize][7].remove_piece().unwrap();
-                            rook.mark_moved();
-                            self.spaces[y1 as usize][5].set_piece(Some(rook));
-                        } else {
-                            let mut rook = self.spaces[y1 as usize][0].remove_piece().unwrap();
-                            rook.mark_moved();
-                            self.spaces[y1 as usize][3].set_piece(Some(rook));
-                        }
-                        self.toggle_turn();
-                        // undo this move if it has put the player in check (tho castling should check for this already)
-                        if self.is_in_check(color) {
-                            self.undo_last_move();
-                            return false;
-                        }
-                        return true;
-                    }
-                }
+        if piece.piece_type() == PieceType::King && y1 == y2 && (x1 + 2 == x2 || x2 + 2 == x1) {
+            let kingside = x1 + 2 == x2;
+            if !self.can_castle(color, kingside) {
+                return Err(MoveError::NoCastlingRights);
+            }
+            // every space between the king and rook must be empty
+            let between = if kingside { 5..7 } else { 1..4 };
+            if between
+                .into_iter()
+                .any(|x| self.space(x, y1).piece().is_some())
+            {
+                return Err(MoveError::CastlingBlocked);
+            }
+            // king cannot move out of, through, or into check
+            if self.is_space_attacked(x1, y1, color) {
+                return Err(MoveError::CastlingOutOfCheck);
             }
+            if self.is_space_attacked(u8::midpoint(x1, x2), y1, color) {
+                return Err(MoveError::CastlingThroughCheck);
+            }
+            if self.is_space_attacked(x2, y1, color) {
+                return Err(MoveError::LeavesKingInCheck);
+            }
+            let mut piece = self.take_piece(x1, y1).unwrap();
+            self.moves.push(MoveRecord::new(
+                x1,
+                y1,
+                x2,
+                y2,
+                None,
+                piece.piece_type(),
+                !piece.has_moved(),
+                None,
+                false,
+                self.halfmove_clock,
+            ));
+            piece.mark_moved();
+            self.put_piece(x2, y2, piece);
+            if kingside {
+                let mut rook = self.take_piece(7, y1).unwrap();
+                rook.mark_moved();
+                self.put_piece(5, y1, rook);
+            } else {
+                let mut rook = self.take_piece(0, y1).unwrap();
+                rook.mark_moved();
+                self.put_piece(3, y1, rook);
+            }
+            self.finish_move();
+            // undo this move if it has put the player in check (tho castling should check for this already)
+            if self.is_in_check(color) {
+                self.unmake();
+                return Err(MoveError::LeavesKingInCheck);
+            }
+            return Ok(MoveOutcome::Castled);
         }
 
         if !match piece.piece_type() {
@@ -298,13 +492,15 @@ impl Board {
             PieceType::King => self.king_can_move(x1, y1, x2, y2),
             PieceType::Knight => self.knight_can_move(x1, y1, x2, y2),
         } {
-            return false;
+            return Err(self.movement_error(x1, y1, x2, y2));
+        }
+        if is_promotion && mv.promotion().is_none() {
+            return Err(MoveError::PromotionRequired);
         }
+        let capture = piece2.map(Piece::piece_type);
 
-        let mut piece = self.spaces[y1 as usize][x1 as usize]
-            .remove_piece()
-            .unwrap();
-        let piece2 = self.spaces[y2 as usize][x2 as usize].remove_piece();
+        let mut piece = self.take_piece(x1, y1).unwrap();
+        let piece2 = self.take_piece(x2, y2);
         if let Some(piece2) = &piece2 {
             self.record_capture_by(piece.color(), piece2.piece_type());
         }
@@ -316,49 +512,97 @@ impl Board {
             piece2,
             piece.piece_type(),
             !piece.has_moved(),
-            is_promotion,
+            mv.promotion(),
+            false,
+            self.halfmove_clock,
         ));
+        if let Some(promotion) = mv.promotion() {
+            piece = Piece::new(promotion, color);
+        }
         piece.mark_moved();
-        self.spaces[y2 as usize][x2 as usize].set_piece(Some(piece));
-        self.toggle_turn();
+        self.put_piece(x2, y2, piece);
+        self.finish_move();
 
         // undo this move if it has put the player in check
         if self.is_in_check(color) {
-            self.undo_last_move();
-            return false;
+            self.unmake();
+            return Err(MoveError::LeavesKingInCheck);
         }
 
-        true
+        Ok(if let Some(promotion) = mv.promotion() {
+            MoveOutcome::Promoted {
+                piece_type: promotion,
+                capture,
+            }
+        } else if let Some(capture) = capture {
+            MoveOutcome::Captured(capture)
+        } else {
+            MoveOutcome::Moved
+        })
     }
 
-    pub fn promote_pawn(&mut self, x: u8, y: u8, piece_type: PieceType) {
-        let piece = self.spaces[y as usize][x as usize]
-            .remove_piece()
-            .expect("promote called on space without piece");
-        assert_eq!(
-            piece.piece_type(),
-            PieceType::Pawn,
-            "promote called on non-pawn"
-        );
-        assert!(
-            (piece.color() == Color::White && y == 7) || (piece.color() == Color::Black && y == 0),
-            "promote called on ineligible pawn"
-        );
-        let mut new_piece = Piece::new(piece_type, piece.color());
-        new_piece.mark_moved();
-        self.spaces[y as usize][x as usize].set_piece(Some(new_piece));
+    // why the piece at (x1, y1) can't move to (x2, y2), for moves its *_can_move function rejected
+    fn movement_error(&self, x1: u8, y1: u8, x2: u8, y2: u8) -> MoveError {
+        let piece = self.space(x1, y1).piece().unwrap();
+        let piece2 = self.space(x2, y2).piece();
+        if piece2.map(Piece::color) == Some(piece.color()) {
+            return MoveError::OwnPieceAtDestination;
+        }
+        let x_abs = (i16::from(x1) - i16::from(x2)).abs();
+        let y_abs = (i16::from(y1) - i16::from(y2)).abs();
+        let forward = match piece.color() {
+            Color::White => y2 > y1,
+            Color::Black => y2 < y1,
+        };
+        match piece.piece_type() {
+            PieceType::Pawn if forward && x_abs == 0 && (y_abs == 1 || y_abs == 2) => {
+                if y_abs == 2 && piece.has_moved() {
+                    MoveError::InvalidMovement
+                } else {
+                    MoveError::PathBlocked
+                }
+            }
+            PieceType::Rook if x_abs == 0 || y_abs == 0 => MoveError::PathBlocked,
+            PieceType::Bishop if x_abs == y_abs => MoveError::PathBlocked,
+            PieceType::Queen if x_abs == 0 || y_abs == 0 || x_abs == y_abs => {
+                MoveError::PathBlocked
+            }
+            PieceType::King if x_abs <= 1 && y_abs <= 1 => MoveError::LeavesKingInCheck,
+            _ => MoveError::InvalidMovement,
+        }
     }
 
+    // takes back the last move, keeping it so it can be redone
     pub fn undo_last_move(&mut self) {
+        let Some(last_move) = self.moves.last() else {
+            return;
+        };
+        self.redo_moves.push(last_move.as_move());
+        self.unmake();
+    }
+
+    // replays the most recently undone move, returning false if there is nothing to redo
+    pub fn redo_move(&mut self) -> bool {
+        let Some(&mv) = self.redo_moves.last() else {
+            return false;
+        };
+        self.make_move(mv).expect("undone moves are legal to redo");
+        true
+    }
+
+    pub fn redo_moves(&self) -> &[Move] {
+        &self.redo_moves
+    }
+
+    // takes back the last move without keeping it for redo, the counterpart to make
+    pub fn unmake(&mut self) {
         if self.moves.is_empty() {
             return;
         }
         let mut last_move = self.moves.pop().unwrap();
         let (x1, y1) = last_move.origin();
         let (x2, y2) = last_move.dest();
-        let mut piece = self.spaces[y2 as usize][x2 as usize]
-            .remove_piece()
-            .unwrap();
+        let mut piece = self.take_piece(x2, y2).unwrap();
         let is_castle = piece.piece_type() == PieceType::King
             && y1 == y2
             && (i16::from(x1) - i16::from(x2)).abs() == 2;
@@ -381,28 +625,45 @@ impl Board {
                     };
                 }
             }
-            self.spaces[y2 as usize][x2 as usize].set_piece(Some(piece2));
+            if last_move.en_passant() {
+                // the captured pawn was beside the origin, not on the destination
+                self.put_piece(x2, y1, piece2);
+            } else {
+                self.put_piece(x2, y2, piece2);
+            }
         } else if is_castle {
             // move rook as well
-            let (rook_x1, rook_x2) = if x1 < x2 {
-                (7usize, x2 - 1)
-            } else {
-                (0usize, x2 + 1)
-            };
-            let mut rook = self.spaces[y2 as usize][rook_x2 as usize]
-                .remove_piece()
-                .unwrap();
+            let (rook_x1, rook_x2) = if x1 < x2 { (7, x2 - 1) } else { (0, x2 + 1) };
+            let mut rook = self.take_piece(rook_x2, y2).unwrap();
             rook.unmark_moved(); // can only castle if rook was unmoved, reset this
-            self.spaces[y1 as usize][rook_x1].set_piece(Some(rook));
+            self.put_piece(rook_x1, y1, rook);
         }
-        if last_move.promotion() {
+        if last_move.promotion().is_some() {
             let mut new_piece = Piece::new(PieceType::Pawn, piece.color());
-            new_piece.mark_moved();
-            self.spaces[y1 as usize][x1 as usize].set_piece(Some(new_piece));
+            if !last_move.first_move() {
+                new_piece.mark_moved();
+            }
+            self.put_piece(x1, y1, new_piece);
         } else {
-            self.spaces[y1 as usize][x1 as usize].set_piece(Some(piece));
+            self.put_piece(x1, y1, piece);
         }
+        self.halfmove_clock = last_move.halfmove_clock();
+        self.position_hashes.pop();
         self.toggle_turn();
+        debug_assert_eq!(self.bitboards, Bitboards::from_spaces(&self.spaces));
+    }
+
+    // the position before any of the recorded moves were made
+    pub fn starting_board(&self) -> Board {
+        let mut board = self.clone();
+        while !board.moves.is_empty() {
+            board.unmake();
+        }
+        board
+    }
+
+    pub fn moves(&self) -> &[MoveRecord] {
+        &self.moves
     }
 
     pub fn captured_by_white(&self) -> &HashMap<PieceType, u8> {
@@ -414,32 +675,181 @@ impl Board {
     }
 
     pub fn is_in_checkmate(&self, color: Color) -> bool {
-        if !self.is_in_check(color) {
-            return false;
+        color == self.turn_color && self.is_in_check(color) && self.legal_moves().is_empty()
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        if !self.legal_moves().is_empty() {
+            // 150 halfmoves without a pawn move or capture ends the game unless the last move was checkmate
+            if self.halfmove_clock >= 150 {
+                return Some(GameResult::Draw(DrawReason::SeventyFiveMoveRule));
+            }
+            if self.repetition_count() >= 5 {
+                return Some(GameResult::Draw(DrawReason::FivefoldRepetition));
+            }
+            if self.has_insufficient_material() {
+                return Some(GameResult::Draw(DrawReason::InsufficientMaterial));
+            }
+            return None;
+        }
+        if !self.is_in_check(self.turn_color) {
+            return Some(GameResult::Draw(DrawReason::Stalemate));
+        }
+        Some(match self.turn_color {
+            Color::White => GameResult::BlackWins,
+            Color::Black => GameResult::WhiteWins,
+        })
+    }
+
+    // neither side can checkmate: only kings, a single minor piece, or bishops all on one space color
+    pub fn has_insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        let mut bishop_space_colors = vec![];
+        for space in self.spaces.iter().flatten() {
+            if let Some(piece) = space.piece() {
+                match piece.piece_type() {
+                    PieceType::King => {}
+                    PieceType::Knight => knights += 1,
+                    PieceType::Bishop => bishop_space_colors.push(space.color()),
+                    PieceType::Queen | PieceType::Rook | PieceType::Pawn => return false,
+                }
+            }
         }
-        // iterate through every piece of matching color and naively move each one to any valid space and see if check remains
-        for x0 in 0..8 {
-            for y0 in 0..8 {
-                if let Some(piece) = self.space(x0, y0).piece() {
-                    if piece.color() == color {
-                        // this clone and trying EVERY space could probably be improved
-                        let mut new_board = self.clone();
-                        for x1 in 0..8 {
-                            for y1 in 0..8 {
-                                if new_board.move_piece(x0, y0, x1, y1)
-                                    && !new_board.is_in_check(color)
-                                {
-                                    // color would no longer be in check if this move were made
-                                    return false;
-                                }
-                            }
-                        }
+        match (knights, bishop_space_colors.as_slice()) {
+            (0 | 1, []) => true,
+            (0, [first, rest @ ..]) => rest.iter().all(|color| color == first),
+            _ => false,
+        }
+    }
+
+    // draws that a player may claim but which don't end the game on their own
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        if self.halfmove_clock >= 100 {
+            return Some(DrawReason::FiftyMoveRule);
+        }
+        if self.repetition_count() >= 3 {
+            return Some(DrawReason::ThreefoldRepetition);
+        }
+        None
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        for (x, y) in bitboard::spaces(self.bitboards.color(self.turn_color)) {
+            self.push_legal_moves_from(x, y, &mut moves);
+        }
+        moves
+    }
+
+    pub fn legal_moves_from(&self, x: u8, y: u8) -> Vec<Move> {
+        let mut moves = vec![];
+        if self
+            .space(x, y)
+            .piece()
+            .is_some_and(|p| p.color() == self.turn_color)
+        {
+            self.push_legal_moves_from(x, y, &mut moves);
+        }
+        moves
+    }
+
+    // the moves the piece at (x, y) can make by its movement pattern, kept if they leave its king safe
+    fn push_legal_moves_from(&self, x: u8, y: u8, moves: &mut Vec<Move>) {
+        let piece = self.space(x, y).piece().unwrap();
+        let color = piece.color();
+        let piece_type = piece.piece_type();
+        let own = self.bitboards.color(color);
+        let occupied = self.bitboards.occupied();
+        let attacks = self.bitboards.attacks(color, piece_type, x, y);
+        let dests = match piece_type {
+            PieceType::Pawn => {
+                let forward = match color {
+                    Color::White => 1,
+                    Color::Black => -1,
+                };
+                let mut dests = 0;
+                if let Some((x2, y2)) = offset(x, y, 0, forward)
+                    && !bitboard::contains(occupied, x2, y2)
+                {
+                    dests |= bitboard::bit(x2, y2);
+                    if !piece.has_moved()
+                        && let Some((x2, y2)) = offset(x, y, 0, forward * 2)
+                        && !bitboard::contains(occupied, x2, y2)
+                    {
+                        dests |= bitboard::bit(x2, y2);
                     }
                 }
+                let mut targets = occupied & !own;
+                if let Some((x2, y2)) = self.en_passant_target() {
+                    targets |= bitboard::bit(x2, y2);
+                }
+                dests | (attacks & targets)
+            }
+            PieceType::King => {
+                let mut dests = attacks & !own;
+                // king cannot castle out of or through check, and check on the destination
+                // is left to the king safety test below
+                for (kingside, between, through) in [(true, 5..7, 5), (false, 1..4, 3)] {
+                    if self.can_castle(color, kingside)
+                        && between
+                            .into_iter()
+                            .all(|x2| !bitboard::contains(occupied, x2, y))
+                        && !self.is_space_attacked(x, y, color)
+                        && !self.is_space_attacked(through, y, color)
+                    {
+                        dests |= bitboard::bit(if kingside { 6 } else { 2 }, y);
+                    }
+                }
+                dests
+            }
+            _ => attacks & !own,
+        };
+        let last_rank = match color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+        for (x2, y2) in bitboard::spaces(dests) {
+            if !self.leaves_king_safe(x, y, x2, y2) {
+                continue;
+            }
+            if piece_type == PieceType::Pawn && y2 == last_rank {
+                for promotion in [
+                    PieceType::Queen,
+                    PieceType::Rook,
+                    PieceType::Bishop,
+                    PieceType::Knight,
+                ] {
+                    moves.push(Move::new(x, y, x2, y2, Some(promotion)));
+                }
+            } else {
+                moves.push(Move::new(x, y, x2, y2, None));
             }
         }
-        // color was in check after exhausting all possible moves
-        true
+    }
+
+    // whether moving the piece at (x1, y1) to (x2, y2) keeps its king out of check, played out
+    // on a copy of the bitboards rather than the board
+    fn leaves_king_safe(&self, x1: u8, y1: u8, x2: u8, y2: u8) -> bool {
+        let piece = self.space(x1, y1).piece().unwrap();
+        let color = piece.color();
+        let mut bitboards = self.bitboards.clone();
+        bitboards.toggle(color, piece.piece_type(), x1, y1);
+        bitboards.toggle(color, piece.piece_type(), x2, y2);
+        if let Some(captured) = self.space(x2, y2).piece() {
+            bitboards.toggle(captured.color(), captured.piece_type(), x2, y2);
+        } else if piece.piece_type() == PieceType::Pawn && x1 != x2 {
+            // en passant takes the pawn beside the origin
+            let captured = self.space(x2, y1).piece().unwrap();
+            bitboards.toggle(captured.color(), PieceType::Pawn, x2, y1);
+        }
+        let attacker = match color {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        // boards set up without a king can't leave it in check
+        bitboard::spaces(bitboards.pieces(color, PieceType::King))
+            .next()
+            .is_none_or(|(x, y)| !bitboards.is_attacked(x, y, attacker))
     }
 
     fn record_capture_by(&mut self, color: Color, captured_piece_type: PieceType) {
@@ -508,37 +918,10 @@ impl Board {
             return false;
         }
         // Check that there aren't pieces between the origin and destination
-        if x1 == x2 {
-            if y1 < y2 {
-                for i in y1 + 1..y2 {
-                    if self.space(x1, i).piece().is_some() {
-                        return false;
-                    }
-                }
-            } else {
-                for i in y2 + 1..y1 {
-                    if self.space(x1, i).piece().is_some() {
-                        return false;
-                    }
-                }
-            }
-        } else {
-            // y1 == y2
-            if x1 < x2 {
-                for i in x1 + 1..x2 {
-                    if self.space(i, y1).piece().is_some() {
-                        return false;
-                    }
-                }
-            } else {
-                for i in x2 + 1..x1 {
-                    if self.space(i, y1).piece().is_some() {
-                        return false;
-                    }
-                }
-            }
-        }
-        true
+        let attacks = self
+            .bitboards
+            .attacks(piece.color(), PieceType::Rook, x1, y1);
+        bitboard::contains(attacks, x2, y2)
     }
 
     fn bishop_can_move(&self, x1: u8, y1: u8, x2: u8, y2: u8) -> bool {
@@ -557,34 +940,10 @@ impl Board {
             return false;
         }
         // Check that there aren't pieces between the origin and destination
-        if x1 < x2 {
-            if y1 < y2 {
-                for i in 1..x2 - x1 {
-                    if self.space(x1 + i, y1 + i).piece().is_some() {
-                        return false;
-                    }
-                }
-            } else {
-                for i in 1..x2 - x1 {
-                    if self.space(x1 + i, y1 - i).piece().is_some() {
-                        return false;
-                    }
-                }
-            }
-        } else if y1 < y2 {
-            for i in 1..x1 - x2 {
-                if self.space(x1 - i, y1 + i).piece().is_some() {
-                    return false;
-                }
-            }
-        } else {
-            for i in 1..x1 - x2 {
-                if self.space(x1 - i, y1 - i).piece().is_some() {
-                    return false;
-                }
-            }
-        }
-        true
+        let attacks = self
+            .bitboards
+            .attacks(piece.color(), PieceType::Bishop, x1, y1);
+        bitboard::contains(attacks, x2, y2)
     }
 
     fn queen_can_move(&self, x1: u8, y1: u8, x2: u8, y2: u8) -> bool {
@@ -602,9 +961,10 @@ impl Board {
         if self.space(x2, y2).piece().map(Piece::color) == Some(piece.color()) {
             return false;
         }
-        let x_abs = (i16::from(x1) - i16::from(x2)).abs();
-        let y_abs = (i16::from(y1) - i16::from(y2)).abs();
-        if x_abs > 1 || y_abs > 1 {
+        let attacks = self
+            .bitboards
+            .attacks(piece.color(), PieceType::King, x1, y1);
+        if !bitboard::contains(attacks, x2, y2) {
             return false;
         }
         if self.is_space_attacked(x2, y2, piece.color()) {
@@ -624,53 +984,29 @@ impl Board {
         if self.space(x2, y2).piece().map(Piece::color) == Some(piece.color()) {
             return false;
         }
-        let x_abs = (i16::from(x1) - i16::from(x2)).abs();
-        let y_abs = (i16::from(y1) - i16::from(y2)).abs();
-        (x_abs == 2 && y_abs == 1) || (x_abs == 1 && y_abs == 2)
+        let attacks = self
+            .bitboards
+            .attacks(piece.color(), PieceType::Knight, x1, y1);
+        bitboard::contains(attacks, x2, y2)
     }
 
+    // whether any piece of the other color attacks (x, y)
     fn is_space_attacked(&self, x: u8, y: u8, color: Color) -> bool {
-        for x0 in 0..8 {
-            for y0 in 0..8 {
-                if x0 == x && y0 == y {
-                    continue;
-                }
-                if let Some(piece) = self.space(x0, y0).piece() {
-                    if piece.color() != color
-                        && match piece.piece_type() {
-                            PieceType::Pawn => self.pawn_can_move(x0, y0, x, y),
-                            PieceType::Rook => self.rook_can_move(x0, y0, x, y),
-                            PieceType::Bishop => self.bishop_can_move(x0, y0, x, y),
-                            PieceType::Queen => self.queen_can_move(x0, y0, x, y),
-                            PieceType::King => self.king_can_move(x0, y0, x, y),
-                            PieceType::Knight => self.knight_can_move(x0, y0, x0, y),
-                        }
-                    {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
+        let attacker = match color {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        self.bitboards.is_attacked(x, y, attacker)
     }
 
-    fn is_in_check(&self, color: Color) -> bool {
-        //find king
-        let pos = self.spaces.iter().enumerate().find_map(|(y, row)| {
-            row.iter().enumerate().find_map(|(x, space)| {
-                if let Some(piece) = space.piece() {
-                    if piece.piece_type() == PieceType::King && piece.color() == color {
-                        #[allow(clippy::cast_possible_truncation)]
-                        return Some((x as u8, y as u8));
-                    }
-                }
-                None
-            })
-        });
-        // should this panic or simply return false? need to not panic if there are custom boards
-        let pos = pos.unwrap_or_else(|| panic!("unable to find {color:?} king on board"));
+    pub fn king_space(&self, color: Color) -> Option<(u8, u8)> {
+        bitboard::spaces(self.bitboards.pieces(color, PieceType::King)).next()
+    }
 
-        self.is_space_attacked(pos.0, pos.1, color)
+    // boards set up without a king are never in check
+    pub fn is_in_check(&self, color: Color) -> bool {
+        self.king_space(color)
+            .is_some_and(|(x, y)| self.is_space_attacked(x, y, color))
     }
 }
 
@@ -680,6 +1016,27 @@ impl Default for Board {
     }
 }
 
+pub fn space_name(x: u8, y: u8) -> String {
+    format!("{}{}", char::from(b'a' + x), y + 1)
+}
+
+pub fn parse_space_name(name: &str) -> Option<(u8, u8)> {
+    let &[file, rank] = name.as_bytes() else {
+        return None;
+    };
+    if (b'a'..=b'h').contains(&file) && (b'1'..=b'8').contains(&rank) {
+        Some((file - b'a', rank - b'1'))
+    } else {
+        None
+    }
+}
+
+fn offset(x: u8, y: u8, dx: i8, dy: i8) -> Option<(u8, u8)> {
+    let x = x.checked_add_signed(dx)?;
+    let y = y.checked_add_signed(dy)?;
+    if x < 8 && y < 8 { Some((x, y)) } else { None }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -796,8 +1153,8 @@ mod tests {
             vec![(wp, 0, 1), (bp, 1, 3), (wk, 0, 4), (bk, 7, 4)],
             Color::White,
         );
-        assert!(b.move_piece(0, 1, 0, 3));
-        assert!(b.move_piece(1, 3, 0, 2));
+        assert!(b.move_piece(0, 1, 0, 3).is_ok());
+        assert!(b.move_piece(1, 3, 0, 2).is_ok());
         assert!(b.space(0, 3).piece().is_none());
     }
 
@@ -925,7 +1282,7 @@ mod tests {
         let wk = Piece::new(PieceType::King, Color::White);
         let bq = Piece::new(PieceType::Queen, Color::Black);
         let mut b = Board::make_custom(vec![(wk, 4, 0), (bq, 5, 0)], Color::White);
-        assert!(b.move_piece(4, 0, 5, 0));
+        assert!(b.move_piece(4, 0, 5, 0).is_ok());
     }
 
     #[test]
@@ -968,7 +1325,7 @@ mod tests {
         let wk = Piece::new(PieceType::King, Color::White);
         let wr = Piece::new(PieceType::Rook, Color::White);
         let mut b = Board::make_custom(vec![(wk, 4, 0), (wr, 0, 0)], Color::White);
-        assert!(b.move_piece(4, 0, 2, 0));
+        assert!(b.move_piece(4, 0, 2, 0).is_ok());
         assert_eq!(
             b.space(2, 0).piece().as_ref().unwrap().piece_type(),
             PieceType::King
@@ -980,7 +1337,7 @@ mod tests {
         let bk = Piece::new(PieceType::King, Color::Black);
         let br = Piece::new(PieceType::Rook, Color::Black);
         let mut b = Board::make_custom(vec![(bk, 4, 7), (br, 0, 7)], Color::Black);
-        assert!(b.move_piece(4, 7, 2, 7));
+        assert!(b.move_piece(4, 7, 2, 7).is_ok());
         assert_eq!(
             b.space(2, 7).piece().as_ref().unwrap().piece_type(),
             PieceType::King
@@ -996,7 +1353,7 @@ mod tests {
         let wk = Piece::new(PieceType::King, Color::White);
         let wr = Piece::new(PieceType::Rook, Color::White);
         let mut b = Board::make_custom(vec![(wk, 4, 0), (wr, 7, 0)], Color::White);
-        assert!(b.move_piece(4, 0, 6, 0));
+        assert!(b.move_piece(4, 0, 6, 0).is_ok());
         assert_eq!(
             b.space(6, 0).piece().as_ref().unwrap().piece_type(),
             PieceType::King
@@ -1008,7 +1365,7 @@ mod tests {
         let bk = Piece::new(PieceType::King, Color::Black);
         let br = Piece::new(PieceType::Rook, Color::Black);
         let mut b = Board::make_custom(vec![(bk, 4, 7), (br, 7, 7)], Color::Black);
-        assert!(b.move_piece(4, 7, 6, 7));
+        assert!(b.move_piece(4, 7, 6, 7).is_ok());
         assert_eq!(
             b.space(6, 7).piece().as_ref().unwrap().piece_type(),
             PieceType::King
@@ -1028,10 +1385,13 @@ mod tests {
             vec![(wk, 4, 0), (wr.clone(), 7, 0), (br.clone(), 5, 2)],
             Color::White,
         );
-        assert!(!b.move_piece(4, 0, 6, 0));
+        assert_eq!(
+            b.move_piece(4, 0, 6, 0),
+            Err(MoveError::CastlingThroughCheck)
+        );
         let bk = Piece::new(PieceType::King, Color::Black);
         let mut b = Board::make_custom(vec![(bk, 4, 7), (br, 7, 7), (wr, 5, 5)], Color::White);
-        assert!(!b.move_piece(4, 7, 6, 7));
+        assert!(b.move_piece(4, 7, 6, 7).is_err());
     }
 
     #[test]
@@ -1049,7 +1409,7 @@ mod tests {
     fn undo_first_move() {
         let mut b = Board::new();
         let b2 = Board::new();
-        assert!(b.move_piece(1, 1, 1, 3));
+        assert!(b.move_piece(1, 1, 1, 3).is_ok());
         assert_ne!(b, b2);
         assert_eq!(b.turn_color, Color::Black);
         b.undo_last_move();
@@ -1061,11 +1421,11 @@ mod tests {
     fn undo_capture() {
         let mut b = Board::new();
         let mut b2 = Board::new();
-        assert!(b.move_piece(1, 1, 1, 3));
-        assert!(b2.move_piece(1, 1, 1, 3));
-        assert!(b.move_piece(2, 6, 2, 4));
-        assert!(b2.move_piece(2, 6, 2, 4));
-        assert!(b.move_piece(1, 3, 2, 4));
+        assert!(b.move_piece(1, 1, 1, 3).is_ok());
+        assert!(b2.move_piece(1, 1, 1, 3).is_ok());
+        assert!(b.move_piece(2, 6, 2, 4).is_ok());
+        assert!(b2.move_piece(2, 6, 2, 4).is_ok());
+        assert!(b.move_piece(1, 3, 2, 4).is_ok());
         assert_ne!(b, b2);
         assert_eq!(b.turn_color, Color::Black);
         b.undo_last_move();
@@ -1079,7 +1439,7 @@ mod tests {
         let wr = Piece::new(PieceType::Rook, Color::White);
         let mut b = Board::make_custom(vec![(wk, 4, 0), (wr, 7, 0)], Color::White);
         let b2 = b.clone();
-        assert!(b.move_piece(4, 0, 6, 0));
+        assert!(b.move_piece(4, 0, 6, 0).is_ok());
         assert_eq!(b.turn_color, Color::Black);
         b.undo_last_move();
         assert_eq!(b, b2);
@@ -1089,7 +1449,7 @@ mod tests {
         let br = Piece::new(PieceType::Rook, Color::Black);
         let mut b = Board::make_custom(vec![(bk, 4, 7), (br, 0, 7)], Color::Black);
         let b2 = b.clone();
-        assert!(b.move_piece(4, 7, 2, 7));
+        assert!(b.move_piece(4, 7, 2, 7).is_ok());
         assert_eq!(b.turn_color, Color::White);
         b.undo_last_move();
         assert_eq!(b, b2);
@@ -1099,12 +1459,12 @@ mod tests {
     #[test]
     fn prevent_move_exposing_check() {
         let mut b = Board::new();
-        assert!(b.move_piece(4, 1, 4, 2));
-        assert!(b.move_piece(2, 6, 2, 5));
-        assert!(b.move_piece(4, 2, 4, 3));
-        assert!(b.move_piece(3, 7, 0, 4));
+        assert!(b.move_piece(4, 1, 4, 2).is_ok());
+        assert!(b.move_piece(2, 6, 2, 5).is_ok());
+        assert!(b.move_piece(4, 2, 4, 3).is_ok());
+        assert!(b.move_piece(3, 7, 0, 4).is_ok());
         let b2 = b.clone();
-        assert!(!b.move_piece(3, 1, 3, 2)); // cannot move this pawn as it would expose king to check from queen
+        assert!(b.move_piece(3, 1, 3, 2).is_err()); // cannot move this pawn as it would expose king to check from queen
         assert_eq!(b, b2);
     }
 
@@ -1112,15 +1472,59 @@ mod tests {
     fn promote() {
         let wp = Piece::new(PieceType::Pawn, Color::White);
         let bp = Piece::new(PieceType::Pawn, Color::Black);
-        let mut b = Board::make_custom(vec![(wp, 0, 7), (bp, 0, 0)], Color::White);
+        let wk = Piece::new(PieceType::King, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let mut b = Board::make_custom(
+            vec![
+                (wp, 0, 6),
+                (bp, 7, 1),
+                (wk.clone(), 4, 0),
+                (bk.clone(), 4, 7),
+            ],
+            Color::White,
+        );
+        let before = b.clone();
+        assert_eq!(b.move_piece(0, 6, 0, 7), Err(MoveError::PromotionRequired));
+        assert_eq!(
+            b.make_move(Move::new(0, 6, 0, 7, Some(PieceType::King))),
+            Err(MoveError::InvalidPromotion)
+        );
+        assert_eq!(
+            b.make_move(Move::new(4, 0, 4, 1, Some(PieceType::Queen))),
+            Err(MoveError::InvalidPromotion)
+        );
+        assert_eq!(b, before);
+        assert_eq!(
+            b.make_move(Move::new(0, 6, 0, 7, Some(PieceType::Bishop))),
+            Ok(MoveOutcome::Promoted {
+                piece_type: PieceType::Bishop,
+                capture: None
+            })
+        );
+        assert_eq!(
+            b.moves().last().unwrap().promotion(),
+            Some(PieceType::Bishop)
+        );
+        assert!(
+            b.make_move(Move::new(7, 1, 7, 0, Some(PieceType::Rook)))
+                .is_ok()
+        );
+
         let mut wb = Piece::new(PieceType::Bishop, Color::White);
         wb.mark_moved();
         let mut br = Piece::new(PieceType::Rook, Color::Black);
         br.mark_moved();
-        let b2 = Board::make_custom(vec![(wb, 0, 7), (br, 0, 0)], Color::White);
-        b.promote_pawn(0, 7, PieceType::Bishop);
-        b.promote_pawn(0, 0, PieceType::Rook);
-        assert_eq!(b, b2);
+        let b2 = Board::make_custom(
+            vec![(wb, 0, 7), (br, 7, 0), (wk, 4, 0), (bk, 4, 7)],
+            Color::White,
+        );
+        assert_eq!(b.spaces(), b2.spaces());
+        // the rook on h1 now checks the king along the first rank
+        assert!(b.is_in_check(Color::White));
+
+        b.undo_last_move();
+        b.undo_last_move();
+        assert_eq!(b, before);
     }
 
     #[test]
@@ -1146,15 +1550,15 @@ mod tests {
         );
         assert!(b.captured_by_white.is_empty());
         assert!(b.captured_by_black.is_empty());
-        assert!(b.move_piece(0, 0, 0, 1));
+        assert!(b.move_piece(0, 0, 0, 1).is_ok());
         assert_eq!(b.captured_by_white.get(&PieceType::Pawn).unwrap(), &1);
         assert!(b.captured_by_black.is_empty());
-        assert!(b.move_piece(2, 1, 1, 1));
+        assert!(b.move_piece(2, 1, 1, 1).is_ok());
         assert_eq!(b.captured_by_black.get(&PieceType::Pawn).unwrap(), &1);
-        assert!(b.move_piece(0, 1, 1, 1));
+        assert!(b.move_piece(0, 1, 1, 1).is_ok());
         assert_eq!(b.captured_by_white.get(&PieceType::Rook).unwrap(), &1);
-        assert!(b.move_piece(1, 4, 1, 3));
-        assert!(b.move_piece(1, 1, 1, 3));
+        assert!(b.move_piece(1, 4, 1, 3).is_ok());
+        assert!(b.move_piece(1, 1, 1, 3).is_ok());
         assert_eq!(b.captured_by_white.get(&PieceType::Pawn).unwrap(), &2);
         b.undo_last_move();
         assert_eq!(b.captured_by_white.get(&PieceType::Pawn).unwrap(), &1);
@@ -1166,14 +1570,14 @@ mod tests {
     #[test]
     fn four_move_checkmate() {
         let mut b = Board::new();
-        assert!(b.move_piece(4, 1, 4, 3));
-        assert!(b.move_piece(0, 6, 0, 5));
-        assert!(b.move_piece(5, 0, 2, 3));
-        assert!(b.move_piece(0, 5, 0, 4));
-        assert!(b.move_piece(3, 0, 7, 4));
-        assert!(b.move_piece(0, 4, 0, 3));
+        assert!(b.move_piece(4, 1, 4, 3).is_ok());
+        assert!(b.move_piece(0, 6, 0, 5).is_ok());
+        assert!(b.move_piece(5, 0, 2, 3).is_ok());
+        assert!(b.move_piece(0, 5, 0, 4).is_ok());
+        assert!(b.move_piece(3, 0, 7, 4).is_ok());
+        assert!(b.move_piece(0, 4, 0, 3).is_ok());
         assert!(!b.is_in_checkmate(Color::Black));
-        assert!(b.move_piece(7, 4, 5, 6));
+        assert!(b.move_piece(7, 4, 5, 6).is_ok());
         assert!(b.is_in_checkmate(Color::Black));
     }
 
@@ -1182,16 +1586,423 @@ mod tests {
         // bug found while playing around
         // king should be able to capture unprotected piece that has cornered it, but is instead marked as in checkmate
         let mut b = Board::new();
-        assert!(b.move_piece(4, 1, 4, 2));
-        assert!(b.move_piece(0, 6, 0, 5));
-        assert!(b.move_piece(3, 0, 6, 3));
-        assert!(b.move_piece(0, 5, 0, 4));
-        assert!(b.move_piece(6, 3, 6, 6));
-        assert!(b.move_piece(0, 4, 0, 3));
+        assert!(b.move_piece(4, 1, 4, 2).is_ok());
+        assert!(b.move_piece(0, 6, 0, 5).is_ok());
+        assert!(b.move_piece(3, 0, 6, 3).is_ok());
+        assert!(b.move_piece(0, 5, 0, 4).is_ok());
+        assert!(b.move_piece(6, 3, 6, 6).is_ok());
+        assert!(b.move_piece(0, 4, 0, 3).is_ok());
         assert!(!b.is_in_checkmate(b.turn_color()));
-        assert!(b.move_piece(6, 6, 5, 7));
+        assert!(b.move_piece(6, 6, 5, 7).is_ok());
         // at this point white queen is in back row and adjacent to black king
         // black isnt in checkmate but only move is to capture WQ with BK
         assert!(!b.is_in_checkmate(b.turn_color()));
     }
+
+    #[test]
+    fn move_errors() {
+        let mut b = Board::new();
+        assert_eq!(b.move_piece(4, 1, 4, 1), Err(MoveError::SameSpace));
+        assert_eq!(b.move_piece(4, 3, 4, 4), Err(MoveError::NoPiece));
+        assert_eq!(b.move_piece(4, 6, 4, 5), Err(MoveError::WrongTurn));
+        assert_eq!(
+            b.move_piece(0, 0, 0, 1),
+            Err(MoveError::OwnPieceAtDestination)
+        );
+        assert_eq!(b.move_piece(6, 0, 6, 2), Err(MoveError::InvalidMovement));
+        assert_eq!(b.move_piece(0, 0, 0, 4), Err(MoveError::PathBlocked));
+        assert_eq!(b.move_piece(4, 0, 6, 0), Err(MoveError::CastlingBlocked));
+        assert_eq!(b.move_piece(4, 1, 4, 3), Ok(MoveOutcome::Moved));
+        assert_eq!(b.move_piece(3, 6, 3, 4), Ok(MoveOutcome::Moved));
+        assert_eq!(
+            b.move_piece(4, 3, 3, 4),
+            Ok(MoveOutcome::Captured(PieceType::Pawn))
+        );
+        assert_eq!(b.move_piece(3, 7, 3, 0), Err(MoveError::PathBlocked));
+        assert_eq!(b.move_piece(4, 6, 4, 4), Ok(MoveOutcome::Moved));
+        assert_eq!(b.move_piece(3, 4, 4, 5), Ok(MoveOutcome::EnPassant));
+        assert_eq!(b.move_piece(5, 7, 1, 3), Ok(MoveOutcome::Moved));
+        // the white d pawn is pinned
+        assert_eq!(b.move_piece(3, 1, 3, 2), Err(MoveError::LeavesKingInCheck));
+
+        let mut b = Board::from_fen("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+        assert_eq!(b.move_piece(4, 0, 3, 0), Err(MoveError::LeavesKingInCheck));
+        assert_eq!(b.move_piece(4, 0, 3, 1), Ok(MoveOutcome::Moved));
+
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Qk - 0 1").unwrap();
+        assert_eq!(
+            b.clone().move_piece(4, 0, 6, 0),
+            Err(MoveError::NoCastlingRights)
+        );
+        assert_eq!(b.clone().move_piece(4, 0, 2, 0), Ok(MoveOutcome::Castled));
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R2QK2R b KQkq - 0 1").unwrap();
+        assert_eq!(
+            b.clone().move_piece(4, 7, 2, 7),
+            Err(MoveError::CastlingThroughCheck)
+        );
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3KQ1R b KQkq - 0 1").unwrap();
+        assert_eq!(
+            b.clone().move_piece(4, 7, 6, 7),
+            Err(MoveError::CastlingThroughCheck)
+        );
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3KR2 b Qkq - 0 1").unwrap();
+        assert_eq!(b.clone().move_piece(4, 7, 2, 7), Ok(MoveOutcome::Castled));
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K1R1 b Qkq - 0 1").unwrap();
+        assert_eq!(
+            b.clone().move_piece(4, 7, 6, 7),
+            Err(MoveError::LeavesKingInCheck)
+        );
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3KR2 b Qkq - 0 1").unwrap();
+        assert_eq!(
+            b.clone().move_piece(4, 7, 6, 7),
+            Err(MoveError::CastlingThroughCheck)
+        );
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3R1K1 b kq - 0 1").unwrap();
+        assert_eq!(
+            b.clone().move_piece(4, 7, 6, 7),
+            Err(MoveError::CastlingOutOfCheck)
+        );
+    }
+
+    #[test]
+    fn undo_en_passant() {
+        let wp = Piece::new(PieceType::Pawn, Color::White);
+        let bp = Piece::new(PieceType::Pawn, Color::Black);
+        let wk = Piece::new(PieceType::King, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let mut b = Board::make_custom(
+            vec![(wp, 0, 1), (bp, 1, 3), (wk, 0, 4), (bk, 7, 4)],
+            Color::White,
+        );
+        assert!(b.move_piece(0, 1, 0, 3).is_ok());
+        let b2 = b.clone();
+        assert!(b.move_piece(1, 3, 0, 2).is_ok());
+        b.undo_last_move();
+        assert_eq!(b, b2);
+    }
+
+    #[test]
+    fn make_and_unmake_skip_redo() {
+        let mut b = Board::new();
+        let e4 = Move::new(4, 1, 4, 3, None);
+        b.make_move(e4).unwrap();
+        b.undo_last_move();
+        b.make(Move::new(3, 1, 3, 3, None)).unwrap();
+        assert_eq!(b.legal_moves().len(), 20);
+        b.unmake();
+        assert_eq!(b, Board::new());
+        assert_eq!(b.redo_moves(), [e4]);
+    }
+
+    #[test]
+    fn redo() {
+        let mut b = Board::new();
+        assert!(!b.redo_move());
+        assert!(b.move_piece(4, 1, 4, 3).is_ok());
+        assert!(b.move_piece(4, 6, 4, 4).is_ok());
+        assert!(b.move_piece(6, 0, 5, 2).is_ok());
+        let after = b.clone();
+        b.undo_last_move();
+        b.undo_last_move();
+        assert_eq!(b.redo_moves().len(), 2);
+        assert!(b.redo_move());
+        assert!(b.redo_move());
+        assert!(!b.redo_move());
+        assert_eq!(b, after);
+
+        // replaying the undone move by hand keeps the rest of the stack
+        b.undo_last_move();
+        b.undo_last_move();
+        assert!(b.move_piece(4, 6, 4, 4).is_ok());
+        assert_eq!(b.redo_moves(), &[Move::new(6, 0, 5, 2, None)]);
+
+        // a different move clears it
+        b.undo_last_move();
+        assert!(b.move_piece(3, 6, 3, 4).is_ok());
+        assert!(b.redo_moves().is_empty());
+        assert!(!b.redo_move());
+
+        // illegal moves leave it alone
+        b.undo_last_move();
+        assert!(b.move_piece(4, 3, 4, 5).is_err());
+        assert_eq!(b.redo_moves(), &[Move::new(3, 6, 3, 4, None)]);
+    }
+
+    #[test]
+    fn redo_promotion() {
+        let mut b = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(
+            b.make_move(Move::new(0, 6, 0, 7, Some(PieceType::Rook)))
+                .is_ok()
+        );
+        let after = b.clone();
+        b.undo_last_move();
+        assert!(b.redo_move());
+        assert_eq!(b, after);
+    }
+
+    #[test]
+    fn king_space() {
+        let b = Board::new();
+        assert_eq!(b.king_space(Color::White), Some((4, 0)));
+        assert_eq!(b.king_space(Color::Black), Some((4, 7)));
+        let wk = Piece::new(PieceType::King, Color::White);
+        let b = Board::make_custom(vec![(wk, 2, 5)], Color::White);
+        assert_eq!(b.king_space(Color::White), Some((2, 5)));
+        assert_eq!(b.king_space(Color::Black), None);
+        assert!(!b.is_in_check(Color::Black));
+    }
+
+    #[test]
+    fn knight_gives_check() {
+        let wk = Piece::new(PieceType::King, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let bn = Piece::new(PieceType::Knight, Color::Black);
+        let b = Board::make_custom(vec![(wk, 4, 0), (bk, 4, 7), (bn, 5, 2)], Color::White);
+        assert!(b.is_in_check(Color::White));
+    }
+
+    #[test]
+    fn cant_castle_queenside_past_knight() {
+        let wk = Piece::new(PieceType::King, Color::White);
+        let wr = Piece::new(PieceType::Rook, Color::White);
+        let wn = Piece::new(PieceType::Knight, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let mut b = Board::make_custom(
+            vec![(wk, 4, 0), (wr, 0, 0), (wn, 1, 0), (bk, 4, 7)],
+            Color::White,
+        );
+        assert!(b.move_piece(4, 0, 2, 0).is_err());
+    }
+
+    #[test]
+    fn starting_legal_moves() {
+        let b = Board::new();
+        assert_eq!(b.legal_moves().len(), 20);
+        assert_eq!(
+            b.legal_moves_from(6, 0),
+            vec![Move::new(6, 0, 5, 2, None), Move::new(6, 0, 7, 2, None)]
+        );
+        assert!(b.legal_moves_from(4, 0).is_empty());
+        assert!(b.legal_moves_from(4, 6).is_empty());
+    }
+
+    #[test]
+    fn legal_moves_include_special_moves() {
+        let wk = Piece::new(PieceType::King, Color::White);
+        let wr = Piece::new(PieceType::Rook, Color::White);
+        let wp = Piece::new(PieceType::Pawn, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let bp = Piece::new(PieceType::Pawn, Color::Black);
+        let mut b = Board::make_custom(
+            vec![
+                (wk, 4, 0),
+                (wr, 7, 0),
+                (wp.clone(), 0, 6),
+                (wp, 3, 4),
+                (bk, 6, 7),
+                (bp, 4, 6),
+            ],
+            Color::Black,
+        );
+        assert!(b.move_piece(4, 6, 4, 4).is_ok());
+        let moves = b.legal_moves();
+        assert!(moves.contains(&Move::new(4, 0, 6, 0, None)));
+        assert!(moves.contains(&Move::new(3, 4, 4, 5, None)));
+        for promotion in [
+            PieceType::Queen,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+        ] {
+            assert!(moves.contains(&Move::new(0, 6, 0, 7, Some(promotion))));
+        }
+        assert!(!moves.contains(&Move::new(0, 6, 0, 7, None)));
+    }
+
+    #[test]
+    fn legal_moves_escape_check() {
+        let mut b = Board::new();
+        assert!(b.move_piece(4, 1, 4, 3).is_ok());
+        assert!(b.move_piece(5, 6, 5, 5).is_ok());
+        assert!(b.move_piece(3, 0, 7, 4).is_ok());
+        // black can only block with the g pawn
+        assert_eq!(b.legal_moves(), vec![Move::new(6, 6, 6, 5, None)]);
+    }
+
+    #[test]
+    fn stalemate_is_draw() {
+        let wk = Piece::new(PieceType::King, Color::White);
+        let wq = Piece::new(PieceType::Queen, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let mut b = Board::make_custom(vec![(wk, 2, 5), (wq, 1, 3), (bk, 0, 7)], Color::White);
+        assert_eq!(b.result(), None);
+        assert!(b.move_piece(1, 3, 1, 5).is_ok());
+        assert!(!b.is_in_checkmate(Color::Black));
+        assert_eq!(b.result(), Some(GameResult::Draw(DrawReason::Stalemate)));
+    }
+
+    #[test]
+    fn checkmate_result() {
+        let mut b = Board::new();
+        assert!(b.move_piece(5, 1, 5, 2).is_ok());
+        assert!(b.move_piece(4, 6, 4, 4).is_ok());
+        assert!(b.move_piece(6, 1, 6, 3).is_ok());
+        assert_eq!(b.result(), None);
+        assert!(b.move_piece(3, 7, 7, 3).is_ok());
+        assert_eq!(b.result(), Some(GameResult::BlackWins));
+    }
+
+    #[test]
+    fn halfmove_clock() {
+        let mut b = Board::new();
+        assert!(b.move_piece(6, 0, 5, 2).is_ok());
+        assert!(b.move_piece(6, 7, 5, 5).is_ok());
+        assert_eq!(b.halfmove_clock(), 2);
+        assert!(b.move_piece(4, 1, 4, 3).is_ok());
+        assert_eq!(b.halfmove_clock(), 0);
+        assert!(b.move_piece(5, 5, 4, 3).is_ok());
+        assert_eq!(b.halfmove_clock(), 0);
+        assert!(b.move_piece(1, 0, 2, 2).is_ok());
+        assert_eq!(b.halfmove_clock(), 1);
+        b.undo_last_move();
+        b.undo_last_move();
+        b.undo_last_move();
+        assert_eq!(b.halfmove_clock(), 2);
+    }
+
+    #[test]
+    fn fifty_and_seventy_five_move_rules() {
+        let wk = Piece::new(PieceType::King, Color::White);
+        let wr = Piece::new(PieceType::Rook, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let mut b = Board::make_custom(vec![(wk, 0, 0), (wr, 7, 0), (bk, 4, 7)], Color::White);
+        b.halfmove_clock = 98;
+        assert!(b.move_piece(7, 0, 7, 1).is_ok());
+        assert_eq!(b.claimable_draw(), None);
+        assert!(b.move_piece(4, 7, 3, 7).is_ok());
+        assert_eq!(b.claimable_draw(), Some(DrawReason::FiftyMoveRule));
+        assert_eq!(b.result(), None);
+        b.halfmove_clock = 148;
+        assert!(b.move_piece(7, 1, 7, 2).is_ok());
+        assert_eq!(b.result(), None);
+        assert!(b.move_piece(3, 7, 4, 7).is_ok());
+        assert_eq!(
+            b.result(),
+            Some(GameResult::Draw(DrawReason::SeventyFiveMoveRule))
+        );
+        b.undo_last_move();
+        assert_eq!(b.halfmove_clock(), 149);
+    }
+
+    #[test]
+    fn threefold_and_fivefold_repetition() {
+        let mut b = Board::new();
+        let start = b.position_hash();
+        for i in 0..16 {
+            assert_eq!(b.claimable_draw().is_some(), i >= 8);
+            assert_eq!(b.result(), None);
+            match i % 4 {
+                0 => assert!(b.move_piece(6, 0, 5, 2).is_ok()),
+                1 => assert!(b.move_piece(6, 7, 5, 5).is_ok()),
+                2 => assert!(b.move_piece(5, 2, 6, 0).is_ok()),
+                _ => assert!(b.move_piece(5, 5, 6, 7).is_ok()),
+            }
+        }
+        assert_eq!(b.position_hash(), start);
+        assert_eq!(b.repetition_count(), 5);
+        assert_eq!(b.claimable_draw(), Some(DrawReason::ThreefoldRepetition));
+        assert_eq!(
+            b.result(),
+            Some(GameResult::Draw(DrawReason::FivefoldRepetition))
+        );
+        b.undo_last_move();
+        assert_eq!(b.repetition_count(), 4);
+        assert_eq!(b.result(), None);
+    }
+
+    #[test]
+    fn lost_castling_rights_change_position() {
+        let mut b = Board::new();
+        assert!(b.move_piece(7, 1, 7, 3).is_ok());
+        assert!(b.move_piece(7, 6, 7, 4).is_ok());
+        let before = b.position_hash();
+        assert!(b.move_piece(7, 0, 7, 1).is_ok());
+        assert!(b.move_piece(7, 7, 7, 6).is_ok());
+        assert!(b.move_piece(7, 1, 7, 0).is_ok());
+        assert!(b.move_piece(7, 6, 7, 7).is_ok());
+        assert_ne!(b.position_hash(), before);
+        assert_eq!(b.repetition_count(), 1);
+    }
+
+    #[test]
+    fn en_passant_changes_position_only_when_capturable() {
+        let mut b = Board::new();
+        assert!(b.move_piece(4, 1, 4, 3).is_ok());
+        let b2 = Board::from_strs(&[
+            "rnbqkbnr", "pppppppp", "________", "________", "____P___", "________", "PPPP_PPP",
+            "RNBQKBNR", "B",
+        ]);
+        assert_eq!(b.position_hash(), b2.position_hash());
+
+        assert!(b.move_piece(0, 6, 0, 5).is_ok());
+        assert!(b.move_piece(4, 3, 4, 4).is_ok());
+        assert!(b.move_piece(3, 6, 3, 4).is_ok());
+        let b2 = Board::from_strs(&[
+            "rnbqkbnr", "_pp_pppp", "p_______", "___pP___", "________", "________", "PPPP_PPP",
+            "RNBQKBNR", "W",
+        ]);
+        assert_ne!(b.position_hash(), b2.position_hash());
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let wk = Piece::new(PieceType::King, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let wb = Piece::new(PieceType::Bishop, Color::White);
+        let bb = Piece::new(PieceType::Bishop, Color::Black);
+        let wn = Piece::new(PieceType::Knight, Color::White);
+        let bn = Piece::new(PieceType::Knight, Color::Black);
+        let wp = Piece::new(PieceType::Pawn, Color::White);
+
+        let kings = vec![(wk, 4, 0), (bk, 4, 7)];
+        let with = |extra: Vec<(Piece, u8, u8)>| {
+            let mut placements = kings.clone();
+            placements.extend(extra);
+            Board::make_custom(placements, Color::White)
+        };
+        let b = with(vec![]);
+        assert!(b.has_insufficient_material());
+        assert_eq!(
+            b.result(),
+            Some(GameResult::Draw(DrawReason::InsufficientMaterial))
+        );
+        assert!(with(vec![(wb.clone(), 2, 0)]).has_insufficient_material());
+        assert!(with(vec![(bn.clone(), 1, 7)]).has_insufficient_material());
+        // bishops on c1 and f8 are both on dark spaces
+        assert!(with(vec![(wb.clone(), 2, 0), (bb.clone(), 5, 7)]).has_insufficient_material());
+        assert!(!with(vec![(wb.clone(), 2, 0), (bb, 2, 7)]).has_insufficient_material());
+        assert!(!with(vec![(wn.clone(), 1, 0), (bn, 1, 7)]).has_insufficient_material());
+        assert!(!with(vec![(wn, 1, 0), (wb, 2, 0)]).has_insufficient_material());
+        assert!(!with(vec![(wp, 0, 1)]).has_insufficient_material());
+        assert!(!Board::new().has_insufficient_material());
+    }
+
+    #[test]
+    fn capture_into_insufficient_material() {
+        let wk = Piece::new(PieceType::King, Color::White);
+        let bk = Piece::new(PieceType::King, Color::Black);
+        let wn = Piece::new(PieceType::Knight, Color::White);
+        let br = Piece::new(PieceType::Rook, Color::Black);
+        let mut b = Board::make_custom(
+            vec![(wk, 4, 0), (bk, 4, 7), (wn, 2, 2), (br, 1, 4)],
+            Color::White,
+        );
+        assert_eq!(b.result(), None);
+        assert!(b.move_piece(2, 2, 1, 4).is_ok());
+        assert_eq!(
+            b.result(),
+            Some(GameResult::Draw(DrawReason::InsufficientMaterial))
+        );
+    }
 }
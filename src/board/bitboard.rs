@@ -0,0 +1,291 @@
+use crate::color::Color;
+use crate::piece::PieceType;
+use crate::space::Space;
+
+// a set of spaces, with bit y * 8 + x standing for space (x, y)
+pub type Bitboard = u64;
+
+// north, east, northeast, northwest, then south, west, southwest, southeast, so that the
+// nearest blocker is the lowest set bit for the first four and the highest for the rest
+const DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 0),
+    (1, 1),
+    (-1, 1),
+    (0, -1),
+    (-1, 0),
+    (-1, -1),
+    (1, -1),
+];
+const ROOK_DIRECTIONS: [usize; 4] = [0, 1, 4, 5];
+const BISHOP_DIRECTIONS: [usize; 4] = [2, 3, 6, 7];
+
+const KNIGHT_ATTACKS: [Bitboard; 64] = step_attacks(&[
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+]);
+const KING_ATTACKS: [Bitboard; 64] = step_attacks(&DIRECTIONS);
+const WHITE_PAWN_ATTACKS: [Bitboard; 64] = step_attacks(&[(-1, 1), (1, 1)]);
+const BLACK_PAWN_ATTACKS: [Bitboard; 64] = step_attacks(&[(-1, -1), (1, -1)]);
+// every space in each direction from each space, up to the edge of the board
+const RAYS: [[Bitboard; 64]; 8] = rays();
+
+// every piece on the board by color and type, kept alongside the spaces for fast attack lookups
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bitboards {
+    pieces: [[Bitboard; 6]; 2],
+}
+
+impl Bitboards {
+    pub fn from_spaces(spaces: &[[Space; 8]; 8]) -> Self {
+        let mut bitboards = Self::default();
+        for (y, row) in (0u8..).zip(spaces) {
+            for (x, space) in (0u8..).zip(row) {
+                if let Some(piece) = space.piece() {
+                    bitboards.toggle(piece.color(), piece.piece_type(), x, y);
+                }
+            }
+        }
+        bitboards
+    }
+
+    pub fn pieces(&self, color: Color, piece_type: PieceType) -> Bitboard {
+        self.pieces[color as usize][piece_type as usize]
+    }
+
+    pub fn color(&self, color: Color) -> Bitboard {
+        self.pieces[color as usize]
+            .iter()
+            .fold(0, |all, &b| all | b)
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.color(Color::White) | self.color(Color::Black)
+    }
+
+    // adds the piece to (x, y) if it isn't there, or removes it if it is
+    pub fn toggle(&mut self, color: Color, piece_type: PieceType, x: u8, y: u8) {
+        self.pieces[color as usize][piece_type as usize] ^= bit(x, y);
+    }
+
+    // the spaces a piece of this type and color on (x, y) attacks, whatever is on them
+    pub fn attacks(&self, color: Color, piece_type: PieceType, x: u8, y: u8) -> Bitboard {
+        let square = square(x, y);
+        match piece_type {
+            PieceType::Pawn => match color {
+                Color::White => WHITE_PAWN_ATTACKS[square],
+                Color::Black => BLACK_PAWN_ATTACKS[square],
+            },
+            PieceType::Knight => KNIGHT_ATTACKS[square],
+            PieceType::King => KING_ATTACKS[square],
+            PieceType::Rook => slider_attacks(square, self.occupied(), &ROOK_DIRECTIONS),
+            PieceType::Bishop => slider_attacks(square, self.occupied(), &BISHOP_DIRECTIONS),
+            PieceType::Queen => {
+                slider_attacks(square, self.occupied(), &ROOK_DIRECTIONS)
+                    | slider_attacks(square, self.occupied(), &BISHOP_DIRECTIONS)
+            }
+        }
+    }
+
+    // whether any of color's pieces attack (x, y)
+    pub fn is_attacked(&self, x: u8, y: u8, color: Color) -> bool {
+        // a piece on (x, y) would attack an attacker of the same type standing on any of those
+        // spaces, except pawns which attack in the opposite direction to the defender's pawns
+        let defender = match color {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        let pieces = |piece_type| self.pieces(color, piece_type);
+        let attacks = |piece_type| self.attacks(defender, piece_type, x, y);
+        attacks(PieceType::Pawn) & pieces(PieceType::Pawn) != 0
+            || attacks(PieceType::Knight) & pieces(PieceType::Knight) != 0
+            || attacks(PieceType::King) & pieces(PieceType::King) != 0
+            || attacks(PieceType::Rook) & (pieces(PieceType::Rook) | pieces(PieceType::Queen)) != 0
+            || attacks(PieceType::Bishop) & (pieces(PieceType::Bishop) | pieces(PieceType::Queen))
+                != 0
+    }
+}
+
+pub fn bit(x: u8, y: u8) -> Bitboard {
+    1 << square(x, y)
+}
+
+pub fn contains(bitboard: Bitboard, x: u8, y: u8) -> bool {
+    bitboard & bit(x, y) != 0
+}
+
+// the (x, y) of every space in the set, from a1 to h8
+pub fn spaces(mut bitboard: Bitboard) -> impl Iterator<Item = (u8, u8)> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let square = bitboard.trailing_zeros() as u8;
+        bitboard &= bitboard - 1;
+        Some((square % 8, square / 8))
+    })
+}
+
+fn square(x: u8, y: u8) -> usize {
+    usize::from(y) * 8 + usize::from(x)
+}
+
+fn slider_attacks(square: usize, occupied: Bitboard, directions: &[usize]) -> Bitboard {
+    let mut attacks = 0;
+    for &direction in directions {
+        let ray = RAYS[direction][square];
+        let blockers = ray & occupied;
+        attacks |= ray;
+        if blockers != 0 {
+            // nothing past the nearest blocker is reachable
+            let nearest = if direction < 4 {
+                blockers.trailing_zeros()
+            } else {
+                blockers.ilog2()
+            };
+            attacks &= !RAYS[direction][nearest as usize];
+        }
+    }
+    attacks
+}
+
+// the spaces one step away from each space by each offset
+const fn step_attacks(offsets: &[(i8, i8)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < offsets.len() {
+            table[square] |= step(square, offsets[i].0, offsets[i].1);
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+const fn rays() -> [[Bitboard; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let (dx, dy) = DIRECTIONS[direction];
+        let mut square = 0;
+        while square < 64 {
+            let mut next = step(square, dx, dy);
+            while next != 0 {
+                table[direction][square] |= next;
+                next = step(next.trailing_zeros() as usize, dx, dy);
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+    table
+}
+
+// the space (dx, dy) away from square, or nothing if that is off the board
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+const fn step(square: usize, dx: i8, dy: i8) -> Bitboard {
+    let x = (square % 8) as i8 + dx;
+    let y = (square / 8) as i8 + dy;
+    if x < 0 || x >= 8 || y < 0 || y >= 8 {
+        0
+    } else {
+        1 << (y * 8 + x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::chess_move::Move;
+
+    fn set(names: &[&str]) -> Bitboard {
+        names.iter().fold(0, |set, name| {
+            let (x, y) = crate::board::parse_space_name(name).unwrap();
+            set | bit(x, y)
+        })
+    }
+
+    #[test]
+    fn step_attack_tables() {
+        let b = Bitboards::default();
+        assert_eq!(
+            b.attacks(Color::White, PieceType::Knight, 0, 0),
+            set(&["b3", "c2"])
+        );
+        assert_eq!(
+            b.attacks(Color::Black, PieceType::King, 7, 7),
+            set(&["g8", "g7", "h7"])
+        );
+        assert_eq!(b.attacks(Color::White, PieceType::Pawn, 0, 1), set(&["b3"]));
+        assert_eq!(
+            b.attacks(Color::Black, PieceType::Pawn, 4, 6),
+            set(&["d6", "f6"])
+        );
+    }
+
+    #[test]
+    fn slider_attacks_stop_at_blockers() {
+        let b = Board::from_fen("4k3/8/8/1p6/8/8/8/R3K3 w - - 0 1").unwrap();
+        let bitboards = Bitboards::from_spaces(b.spaces());
+        assert_eq!(
+            bitboards.attacks(Color::White, PieceType::Rook, 0, 0),
+            set(&[
+                "a2", "a3", "a4", "a5", "a6", "a7", "a8", "b1", "c1", "d1", "e1"
+            ])
+        );
+        assert_eq!(
+            bitboards.attacks(Color::White, PieceType::Bishop, 3, 2),
+            set(&["c4", "b5", "e4", "f5", "g6", "h7", "c2", "b1", "e2", "f1"])
+        );
+    }
+
+    #[test]
+    fn attacked_spaces() {
+        let b = Board::from_fen("4k3/8/8/1p6/8/8/8/R3K3 w - - 0 1").unwrap();
+        let bitboards = Bitboards::from_spaces(b.spaces());
+        assert!(bitboards.is_attacked(0, 7, Color::White));
+        assert!(bitboards.is_attacked(0, 3, Color::Black));
+        assert!(bitboards.is_attacked(2, 3, Color::Black));
+        assert!(!bitboards.is_attacked(1, 3, Color::Black));
+        assert!(bitboards.is_attacked(3, 7, Color::Black));
+        assert!(!bitboards.is_attacked(3, 5, Color::White));
+        assert_eq!(
+            spaces(set(&["a1", "h8", "c2"])).collect::<Vec<_>>(),
+            [(0, 0), (2, 1), (7, 7)]
+        );
+    }
+
+    #[test]
+    fn kept_in_sync_with_spaces() {
+        let mut b =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        for mv in [
+            "a2a4", "b4a3", "e1g1", "e8c8", "e5f7", "a3b2", "f7h8", "b2a1n",
+        ] {
+            b.make_move(Move::from_coordinates(mv).unwrap()).unwrap();
+            assert_eq!(b.bitboards(), &Bitboards::from_spaces(b.spaces()), "{mv}");
+        }
+        while !b.moves().is_empty() {
+            b.undo_last_move();
+            assert_eq!(b.bitboards(), &Bitboards::from_spaces(b.spaces()));
+        }
+        // and through the make and unmake the search uses
+        for mv in b.legal_moves() {
+            b.make(mv).unwrap();
+            assert_eq!(b.bitboards(), &Bitboards::from_spaces(b.spaces()), "{mv}");
+            b.unmake();
+            assert_eq!(b.bitboards(), &Bitboards::from_spaces(b.spaces()), "{mv}");
+        }
+    }
+}
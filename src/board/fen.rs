@@ -0,0 +1,456 @@
+use super::bitboard::Bitboards;
+use super::{Board, parse_space_name, space_name};
+use crate::color::Color;
+use crate::piece::{Piece, PieceType};
+use crate::space::Space;
+use std::array::from_fn;
+use std::collections::HashMap;
+use std::fmt;
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FenError {
+    WrongFieldCount(usize),
+    InvalidPlacement(String),
+    InvalidKingCount,
+    PawnOnBackRank,
+    InvalidTurnColor(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    OpponentInCheck,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::WrongFieldCount(count) => {
+                write!(f, "expected 4 to 6 FEN fields, found {count}")
+            }
+            FenError::InvalidPlacement(placement) => {
+                write!(f, "invalid piece placement \"{placement}\"")
+            }
+            FenError::InvalidKingCount => write!(f, "each side must have exactly one king"),
+            FenError::PawnOnBackRank => write!(f, "pawns can't be on the first or last rank"),
+            FenError::InvalidTurnColor(color) => write!(f, "invalid side to move \"{color}\""),
+            FenError::InvalidCastling(castling) => {
+                write!(f, "invalid castling availability \"{castling}\"")
+            }
+            FenError::InvalidEnPassant(target) => {
+                write!(f, "invalid en passant target \"{target}\"")
+            }
+            FenError::InvalidHalfmoveClock(clock) => {
+                write!(f, "invalid halfmove clock \"{clock}\"")
+            }
+            FenError::InvalidFullmoveNumber(number) => {
+                write!(f, "invalid fullmove number \"{number}\"")
+            }
+            FenError::OpponentInCheck => write!(f, "the side not to move is in check"),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl Board {
+    // the halfmove clock and fullmove number may be left off, as they are in EPD
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if !(4..=6).contains(&fields.len()) {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+
+        let mut spaces = parse_placement(fields[0])?;
+        for color in [Color::White, Color::Black] {
+            let kings = spaces
+                .iter()
+                .flatten()
+                .filter_map(Space::piece)
+                .filter(|p| p.piece_type() == PieceType::King && p.color() == color)
+                .count();
+            if kings != 1 {
+                return Err(FenError::InvalidKingCount);
+            }
+        }
+        if spaces[0]
+            .iter()
+            .chain(&spaces[7])
+            .filter_map(Space::piece)
+            .any(|p| p.piece_type() == PieceType::Pawn)
+        {
+            return Err(FenError::PawnOnBackRank);
+        }
+
+        let turn_color = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            color => return Err(FenError::InvalidTurnColor(color.to_string())),
+        };
+
+        mark_moved_pieces(&mut spaces, fields[2])?;
+
+        let starting_en_passant = match fields[3] {
+            "-" => None,
+            target => {
+                let invalid = || FenError::InvalidEnPassant(target.to_string());
+                let (x, y) = parse_space_name(target).ok_or_else(invalid)?;
+                // the pawn that moved must be past the target, with the target and its origin empty
+                let (expected_y, pawn_y, origin_y, pawn_color) = match turn_color {
+                    Color::White => (5, 4, 6, Color::Black),
+                    Color::Black => (2, 3, 1, Color::White),
+                };
+                let pawn_moved = spaces[pawn_y][x as usize]
+                    .piece()
+                    .is_some_and(|p| p.piece_type() == PieceType::Pawn && p.color() == pawn_color);
+                if usize::from(y) != expected_y
+                    || !pawn_moved
+                    || spaces[usize::from(y)][x as usize].piece().is_some()
+                    || spaces[origin_y][x as usize].piece().is_some()
+                {
+                    return Err(invalid());
+                }
+                Some((x, y))
+            }
+        };
+
+        let halfmove_clock = match fields.get(4) {
+            Some(clock) => clock
+                .parse()
+                .map_err(|_| FenError::InvalidHalfmoveClock((*clock).to_string()))?,
+            None => 0,
+        };
+        let starting_fullmove = match fields.get(5) {
+            Some(number) => match number.parse() {
+                Ok(number) if number > 0 => number,
+                _ => return Err(FenError::InvalidFullmoveNumber((*number).to_string())),
+            },
+            None => 1,
+        };
+
+        let mut board = Self {
+            spaces,
+            bitboards: Bitboards::default(),
+            turn_color,
+            moves: vec![],
+            halfmove_clock,
+            starting_fullmove,
+            starting_en_passant,
+            position_hashes: vec![],
+            captured_by_white: HashMap::new(),
+            captured_by_black: HashMap::new(),
+            redo_moves: vec![],
+        };
+        board.set_up();
+        // otherwise the side to move could take the king
+        let waiting_color = match turn_color {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        if board.is_in_check(waiting_color) {
+            return Err(FenError::OpponentInCheck);
+        }
+        Ok(board)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for y in (0..8).rev() {
+            let mut empty = 0;
+            for x in 0..8 {
+                match self.space(x, y).piece() {
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(fen_char(piece));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if y > 0 {
+                placement.push('/');
+            }
+        }
+
+        let turn_color = match self.turn_color {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+
+        let mut castling = String::new();
+        for (color, kingside, c) in [
+            (Color::White, true, 'K'),
+            (Color::White, false, 'Q'),
+            (Color::Black, true, 'k'),
+            (Color::Black, false, 'q'),
+        ] {
+            if self.can_castle(color, kingside) {
+                castling.push(c);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = self
+            .en_passant_target()
+            .map_or_else(|| "-".to_string(), |(x, y)| space_name(x, y));
+
+        format!(
+            "{placement} {turn_color} {castling} {en_passant} {} {}",
+            self.halfmove_clock,
+            self.fullmove_number()
+        )
+    }
+}
+
+fn parse_placement(placement: &str) -> Result<[[Space; 8]; 8], FenError> {
+    let invalid = || FenError::InvalidPlacement(placement.to_string());
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(invalid());
+    }
+    let mut spaces: [[Space; 8]; 8] = from_fn(|row| {
+        from_fn(|col| {
+            let color = if (row + col) % 2 == 0 {
+                Color::Black
+            } else {
+                Color::White
+            };
+            Space::new(color, None)
+        })
+    });
+    for (rank, row) in ranks.iter().zip(spaces.iter_mut().rev()) {
+        let mut x = 0;
+        for c in rank.chars() {
+            if let Some(empty) = c.to_digit(10) {
+                if !(1..=8).contains(&empty) {
+                    return Err(invalid());
+                }
+                x += empty as usize;
+                continue;
+            }
+            let piece_type = match c.to_ascii_lowercase() {
+                'k' => PieceType::King,
+                'q' => PieceType::Queen,
+                'r' => PieceType::Rook,
+                'b' => PieceType::Bishop,
+                'n' => PieceType::Knight,
+                'p' => PieceType::Pawn,
+                _ => return Err(invalid()),
+            };
+            let color = if c.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let space = row.get_mut(x).ok_or_else(invalid)?;
+            space.set_piece(Some(Piece::new(piece_type, color)));
+            x += 1;
+        }
+        if x != 8 {
+            return Err(invalid());
+        }
+    }
+    Ok(spaces)
+}
+
+// FEN doesn't record which pieces have moved, so infer it from castling rights and pawns off their starting rank
+fn mark_moved_pieces(spaces: &mut [[Space; 8]; 8], castling: &str) -> Result<(), FenError> {
+    let invalid = || FenError::InvalidCastling(castling.to_string());
+    let mut rights = vec![];
+    if castling != "-" {
+        for c in castling.chars() {
+            let right = match c {
+                'K' => (Color::White, 7),
+                'Q' => (Color::White, 0),
+                'k' => (Color::Black, 7),
+                'q' => (Color::Black, 0),
+                _ => return Err(invalid()),
+            };
+            if rights.contains(&right) {
+                return Err(invalid());
+            }
+            rights.push(right);
+        }
+    }
+
+    for (y, row) in spaces.iter_mut().enumerate() {
+        for (x, space) in row.iter_mut().enumerate() {
+            let Some(mut piece) = space.remove_piece() else {
+                continue;
+            };
+            let home_rank = match piece.color() {
+                Color::White => 0,
+                Color::Black => 7,
+            };
+            let moved = match piece.piece_type() {
+                PieceType::Pawn => match piece.color() {
+                    Color::White => y != 1,
+                    Color::Black => y != 6,
+                },
+                PieceType::King => {
+                    y != home_rank
+                        || x != 4
+                        || !rights.iter().any(|&(color, _)| color == piece.color())
+                }
+                PieceType::Rook => y != home_rank || !rights.contains(&(piece.color(), x)),
+                _ => false,
+            };
+            if moved {
+                piece.mark_moved();
+            }
+            space.set_piece(Some(piece));
+        }
+    }
+
+    // every right needs its king and rook to still be unmoved on their starting spaces
+    for (color, rook_x) in rights {
+        let home_rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+        let unmoved = |x: usize, piece_type: PieceType| {
+            spaces[home_rank][x].piece().is_some_and(|p| {
+                p.piece_type() == piece_type && p.color() == color && !p.has_moved()
+            })
+        };
+        if !unmoved(4, PieceType::King) || !unmoved(rook_x, PieceType::Rook) {
+            return Err(invalid());
+        }
+    }
+    Ok(())
+}
+
+fn fen_char(piece: &Piece) -> char {
+    match piece.color() {
+        Color::White => piece.draw(),
+        Color::Black => piece.draw().to_ascii_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn starting_position() {
+        let b = Board::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(b, Board::new());
+        assert_eq!(Board::new().to_fen(), STARTING_FEN);
+    }
+
+    #[test]
+    fn round_trip() {
+        for fen in [
+            KIWIPETE,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+            "4k3/8/8/8/8/8/8/4K2R b K - 37 80",
+        ] {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn moves_update_fen() {
+        let mut b = Board::new();
+        assert!(b.move_piece(4, 1, 4, 3).is_ok());
+        assert_eq!(
+            b.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        assert!(b.move_piece(6, 7, 5, 5).is_ok());
+        assert!(b.move_piece(4, 0, 4, 1).is_ok());
+        assert_eq!(
+            b.to_fen(),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 2 2"
+        );
+    }
+
+    #[test]
+    fn en_passant_from_fen() {
+        let mut b =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        assert!(
+            b.legal_moves()
+                .contains(&crate::chess_move::Move::new(4, 4, 5, 5, None))
+        );
+        assert!(b.move_piece(4, 4, 3, 5).is_err());
+        assert!(b.move_piece(4, 4, 5, 5).is_ok());
+        assert!(b.space(5, 4).piece().is_none());
+    }
+
+    #[test]
+    fn castling_rights_from_fen() {
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1").unwrap();
+        assert!(b.can_castle(Color::White, true));
+        assert!(!b.can_castle(Color::White, false));
+        assert!(!b.can_castle(Color::Black, true));
+        assert!(b.can_castle(Color::Black, false));
+        let moves = b.legal_moves();
+        assert!(moves.contains(&crate::chess_move::Move::new(4, 0, 6, 0, None)));
+        assert!(!moves.contains(&crate::chess_move::Move::new(4, 0, 2, 0, None)));
+    }
+
+    #[test]
+    fn invalid_fens() {
+        assert_eq!(
+            Board::from_fen("8/8/8/8 w - -"),
+            Err(FenError::InvalidPlacement("8/8/8/8".to_string()))
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/8 w - - 0 1"),
+            Err(FenError::InvalidKingCount)
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"),
+            Err(FenError::PawnOnBackRank)
+        );
+        assert_eq!(
+            Board::from_fen("3pk3/8/8/8/8/8/8/4K3 b - - 0 1"),
+            Err(FenError::PawnOnBackRank)
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1"),
+            Err(FenError::OpponentInCheck)
+        );
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/4RK2 b - - 0 1").is_ok());
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            Err(FenError::InvalidTurnColor("x".to_string()))
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+            Err(FenError::InvalidCastling("K".to_string()))
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - e6 0 1"),
+            Err(FenError::InvalidEnPassant("e6".to_string()))
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+            Err(FenError::InvalidHalfmoveClock("x".to_string()))
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 0"),
+            Err(FenError::InvalidFullmoveNumber("0".to_string()))
+        );
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3"),
+            Err(FenError::WrongFieldCount(1))
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use super::Board;
+use crate::chess_move::Move;
+
+impl Board {
+    // the number of move paths depth plies long, for checking move generation against known counts
+    pub fn perft(&self, depth: u8) -> u64 {
+        self.clone().perft_nodes(depth)
+    }
+
+    // perft split by the first move of each path, empty at depth 0
+    pub fn divide(&self, depth: u8) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return vec![];
+        }
+        let mut board = self.clone();
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                board.make(mv).expect("legal moves can be made");
+                let nodes = board.perft_nodes(depth - 1);
+                board.unmake();
+                (mv, nodes)
+            })
+            .collect()
+    }
+
+    fn perft_nodes(&mut self, depth: u8) -> u64 {
+        match depth {
+            0 => 1,
+            // the last ply only needs counting, not playing
+            1 => self.legal_moves().len() as u64,
+            _ => {
+                let mut nodes = 0;
+                for mv in self.legal_moves() {
+                    self.make(mv).expect("legal moves can be made");
+                    nodes += self.perft_nodes(depth - 1);
+                    self.unmake();
+                }
+                nodes
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // counts from https://www.chessprogramming.org/Perft_Results
+    fn assert_perft(fen: &str, counts: &[u64]) {
+        let board = Board::from_fen(fen).unwrap();
+        for (depth, &count) in (1..).zip(counts) {
+            assert_eq!(board.perft(depth), count, "depth {depth} of {fen}");
+        }
+        // the board is left as it was
+        assert_eq!(board, Board::from_fen(fen).unwrap());
+    }
+
+    #[test]
+    fn start_position() {
+        assert_perft(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &[20, 400, 8902],
+        );
+        assert_eq!(Board::new().perft(0), 1);
+    }
+
+    #[test]
+    fn kiwipete() {
+        assert_perft(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2039, 97862],
+        );
+    }
+
+    #[test]
+    fn endgame_en_passant() {
+        assert_perft(
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2812],
+        );
+    }
+
+    #[test]
+    fn promotions_and_checks() {
+        assert_perft(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264],
+        );
+        assert_perft(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1486],
+        );
+        assert_perft(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2079],
+        );
+    }
+
+    #[test]
+    fn divide() {
+        let board = Board::new();
+        let divide = board.divide(2);
+        assert_eq!(divide.len(), 20);
+        assert!(divide.iter().all(|&(_, nodes)| nodes == 20));
+        let e4 = Move::from_coordinates("e2e4").unwrap();
+        assert!(divide.contains(&(e4, 20)));
+    }
+}
@@ -0,0 +1,324 @@
+use super::{Board, parse_space_name, space_name};
+use crate::chess_move::Move;
+use crate::color::Color;
+use crate::piece::{Piece, PieceType};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SanError {
+    InvalidSyntax(String),
+    IllegalMove(String),
+    AmbiguousMove(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanError::InvalidSyntax(san) => write!(f, "\"{san}\" is not a valid move"),
+            SanError::IllegalMove(san) => write!(f, "{san} is not a legal move"),
+            SanError::AmbiguousMove(san) => write!(f, "{san} is ambiguous"),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+impl Board {
+    // mv must be legal for the side to move
+    pub fn move_to_san(&self, mv: Move) -> String {
+        let (x1, y1) = mv.origin();
+        let (x2, y2) = mv.dest();
+        let piece_type = self.space(x1, y1).piece().unwrap().piece_type();
+
+        let mut san = if piece_type == PieceType::King && (x1 + 2 == x2 || x2 + 2 == x1) {
+            if x2 > x1 {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            }
+        } else {
+            let is_capture =
+                self.space(x2, y2).piece().is_some() || (piece_type == PieceType::Pawn && x1 != x2);
+            let mut san = String::new();
+            if piece_type == PieceType::Pawn {
+                if is_capture {
+                    san.push(char::from(b'a' + x1));
+                }
+            } else {
+                san.push(piece_letter(piece_type));
+                san.push_str(&self.disambiguation(mv, piece_type));
+            }
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&space_name(x2, y2));
+            if let Some(promotion) = mv.promotion() {
+                san.push('=');
+                san.push(piece_letter(promotion));
+            }
+            san
+        };
+
+        let mut board = self.clone();
+        board
+            .make_move(mv)
+            .expect("move_to_san called with illegal move");
+        if board.is_in_check(board.turn_color) {
+            san.push(if board.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+        san
+    }
+
+    // the origin file and/or rank needed to tell mv apart from other moves of the same piece type to the same space
+    fn disambiguation(&self, mv: Move, piece_type: PieceType) -> String {
+        let (x1, y1) = mv.origin();
+        let others: Vec<(u8, u8)> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|other| {
+                other.dest() == mv.dest()
+                    && other.origin() != mv.origin()
+                    && other.promotion() == mv.promotion()
+                    && self
+                        .space(other.origin().0, other.origin().1)
+                        .piece()
+                        .map(Piece::piece_type)
+                        == Some(piece_type)
+            })
+            .map(Move::origin)
+            .collect();
+        let file = char::from(b'a' + x1);
+        let rank = char::from(b'1' + y1);
+        if others.is_empty() {
+            String::new()
+        } else if others.iter().all(|&(x, _)| x != x1) {
+            file.to_string()
+        } else if others.iter().all(|&(_, y)| y != y1) {
+            rank.to_string()
+        } else {
+            format!("{file}{rank}")
+        }
+    }
+
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let invalid = || SanError::InvalidSyntax(san.to_string());
+        let core = san.trim().trim_end_matches(['+', '#', '!', '?']);
+
+        let castle_dest = match core {
+            "O-O" | "0-0" => Some(6),
+            "O-O-O" | "0-0-0" => Some(2),
+            _ => None,
+        };
+        if let Some(dest_x) = castle_dest {
+            let y = match self.turn_color {
+                Color::White => 0,
+                Color::Black => 7,
+            };
+            let mv = Move::new(4, y, dest_x, y, None);
+            let is_king = self.space(4, y).piece().map(Piece::piece_type) == Some(PieceType::King);
+            return if is_king && self.legal_moves_from(4, y).contains(&mv) {
+                Ok(mv)
+            } else {
+                Err(SanError::IllegalMove(san.to_string()))
+            };
+        }
+
+        let mut chars: Vec<char> = core.chars().collect();
+        let piece_type = match chars.first() {
+            Some('K') => PieceType::King,
+            Some('Q') => PieceType::Queen,
+            Some('R') => PieceType::Rook,
+            Some('B') => PieceType::Bishop,
+            Some('N') => PieceType::Knight,
+            Some(_) => PieceType::Pawn,
+            None => return Err(invalid()),
+        };
+        if piece_type != PieceType::Pawn {
+            chars.remove(0);
+        }
+
+        let promotion = match chars.last() {
+            Some(&c) if "QRBN".contains(c) => {
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+                Some(match c {
+                    'Q' => PieceType::Queen,
+                    'R' => PieceType::Rook,
+                    'B' => PieceType::Bishop,
+                    _ => PieceType::Knight,
+                })
+            }
+            _ => None,
+        };
+
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+        let dest: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let dest = parse_space_name(&dest).ok_or_else(invalid)?;
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in chars {
+            match c {
+                'a'..='h' if from_file.is_none() => from_file = Some(c as u8 - b'a'),
+                '1'..='8' if from_rank.is_none() => from_rank = Some(c as u8 - b'1'),
+                'x' | '-' => {}
+                _ => return Err(invalid()),
+            }
+        }
+
+        let matches: Vec<Move> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| {
+                let (x, y) = mv.origin();
+                mv.dest() == dest
+                    && mv.promotion() == promotion
+                    && self.space(x, y).piece().map(Piece::piece_type) == Some(piece_type)
+                    && from_file.is_none_or(|file| file == x)
+                    && from_rank.is_none_or(|rank| rank == y)
+            })
+            .collect();
+        match matches.as_slice() {
+            [mv] => Ok(*mv),
+            [] => Err(SanError::IllegalMove(san.to_string())),
+            _ => Err(SanError::AmbiguousMove(san.to_string())),
+        }
+    }
+
+    // every move played so far, in SAN, replayed from the starting position
+    pub fn san_moves(&self) -> Vec<String> {
+        let mut board = self.starting_board();
+        self.moves
+            .iter()
+            .map(|record| {
+                let mv = record.as_move();
+                let san = board.move_to_san(mv);
+                board
+                    .make_move(mv)
+                    .expect("recorded moves are legal when replayed");
+                san
+            })
+            .collect()
+    }
+}
+
+fn piece_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::King => 'K',
+        PieceType::Queen => 'Q',
+        PieceType::Rook => 'R',
+        PieceType::Bishop => 'B',
+        PieceType::Knight => 'N',
+        PieceType::Pawn => 'P',
+    }
+}
+
+// plays each move in SAN, checking it is written back the same way, for setting up test positions
+#[cfg(test)]
+impl Board {
+    pub fn play_san(&mut self, moves: &[&str]) {
+        for san in moves {
+            let mv = self.parse_san(san).unwrap();
+            assert_eq!(&self.move_to_san(mv), san);
+            assert!(self.make_move(mv).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opening_moves() {
+        let mut b = Board::new();
+        b.play_san(&["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "O-O"]);
+        assert_eq!(
+            b.san_moves(),
+            vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "O-O"]
+        );
+    }
+
+    #[test]
+    fn check_and_mate() {
+        let mut b = Board::new();
+        b.play_san(&["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]);
+        let mut b = Board::new();
+        b.play_san(&["e4", "f5", "Qh5+"]);
+    }
+
+    #[test]
+    fn disambiguation() {
+        let b = Board::from_fen("4k3/8/8/8/8/8/1R3R2/4K1N1 w - - 0 1").unwrap();
+        assert_eq!(b.move_to_san(Move::new(1, 1, 3, 1, None)), "Rbd2");
+        assert_eq!(b.parse_san("Rfd2"), Ok(Move::new(5, 1, 3, 1, None)));
+        assert_eq!(
+            b.parse_san("Rd2"),
+            Err(SanError::AmbiguousMove("Rd2".to_string()))
+        );
+        assert_eq!(b.move_to_san(Move::new(6, 0, 5, 2, None)), "Nf3");
+
+        let b = Board::from_fen("4k3/8/8/1R6/8/8/1R6/4K3 w - - 0 1").unwrap();
+        assert_eq!(b.move_to_san(Move::new(1, 1, 1, 3, None)), "R2b4");
+
+        let b = Board::from_fen("7k/8/8/8/Q1Q5/8/Q7/4K3 w - - 0 1").unwrap();
+        assert_eq!(b.move_to_san(Move::new(0, 3, 1, 2, None)), "Qa4b3");
+        assert_eq!(b.parse_san("Qa4b3"), Ok(Move::new(0, 3, 1, 2, None)));
+    }
+
+    #[test]
+    fn en_passant_and_promotion() {
+        let b = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+            .unwrap();
+        assert_eq!(b.parse_san("exf6"), Ok(Move::new(4, 4, 5, 5, None)));
+        assert_eq!(b.move_to_san(Move::new(4, 4, 5, 5, None)), "exf6");
+
+        let b = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mv = Move::new(1, 6, 1, 7, Some(PieceType::Queen));
+        assert_eq!(b.move_to_san(mv), "b8=Q+");
+        assert_eq!(b.parse_san("b8=Q+"), Ok(mv));
+        assert_eq!(b.parse_san("b8Q"), Ok(mv));
+        assert_eq!(
+            b.parse_san("b8=N"),
+            Ok(Move::new(1, 6, 1, 7, Some(PieceType::Knight)))
+        );
+        assert_eq!(
+            b.parse_san("b8"),
+            Err(SanError::IllegalMove("b8".to_string()))
+        );
+    }
+
+    #[test]
+    fn queenside_castle() {
+        let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        assert_eq!(b.parse_san("O-O-O"), Ok(Move::new(4, 7, 2, 7, None)));
+        assert_eq!(b.parse_san("0-0"), Ok(Move::new(4, 7, 6, 7, None)));
+        assert_eq!(b.move_to_san(Move::new(4, 7, 2, 7, None)), "O-O-O");
+    }
+
+    #[test]
+    fn invalid_san() {
+        let b = Board::new();
+        assert_eq!(b.parse_san(""), Err(SanError::InvalidSyntax(String::new())));
+        assert_eq!(
+            b.parse_san("Nz9"),
+            Err(SanError::InvalidSyntax("Nz9".to_string()))
+        );
+        assert_eq!(
+            b.parse_san("e5"),
+            Err(SanError::IllegalMove("e5".to_string()))
+        );
+        assert_eq!(
+            b.parse_san("O-O"),
+            Err(SanError::IllegalMove("O-O".to_string()))
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::board::{parse_space_name, space_name};
+use crate::piece::PieceType;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    origin: (u8, u8),
+    dest: (u8, u8),
+    promotion: Option<PieceType>,
+}
+
+impl Move {
+    pub fn new(x1: u8, y1: u8, x2: u8, y2: u8, promotion: Option<PieceType>) -> Self {
+        Self {
+            origin: (x1, y1),
+            dest: (x2, y2),
+            promotion,
+        }
+    }
+
+    pub fn origin(self) -> (u8, u8) {
+        self.origin
+    }
+
+    pub fn dest(self) -> (u8, u8) {
+        self.dest
+    }
+
+    pub fn promotion(self) -> Option<PieceType> {
+        self.promotion
+    }
+
+    // parses coordinate notation like e2e4 or e7e8q, without checking legality
+    pub fn from_coordinates(text: &str) -> Option<Self> {
+        let (spaces, promotion) = match text.len() {
+            4 => (text, None),
+            5 => {
+                let promotion = match text.as_bytes()[4].to_ascii_lowercase() {
+                    b'q' => PieceType::Queen,
+                    b'r' => PieceType::Rook,
+                    b'b' => PieceType::Bishop,
+                    b'n' => PieceType::Knight,
+                    _ => return None,
+                };
+                (text.get(..4)?, Some(promotion))
+            }
+            _ => return None,
+        };
+        let (x1, y1) = parse_space_name(spaces.get(..2)?)?;
+        let (x2, y2) = parse_space_name(spaces.get(2..)?)?;
+        Some(Self::new(x1, y1, x2, y2, promotion))
+    }
+}
+
+// coordinate notation, as used by UCI
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x1, y1) = self.origin;
+        let (x2, y2) = self.dest;
+        write!(f, "{}{}", space_name(x1, y1), space_name(x2, y2))?;
+        match self.promotion {
+            Some(PieceType::Queen) => write!(f, "q"),
+            Some(PieceType::Rook) => write!(f, "r"),
+            Some(PieceType::Bishop) => write!(f, "b"),
+            Some(PieceType::Knight) => write!(f, "n"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveOutcome {
+    Moved,
+    Captured(PieceType),
+    EnPassant,
+    Castled,
+    Promoted {
+        piece_type: PieceType,
+        capture: Option<PieceType>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    SameSpace,
+    NoPiece,
+    WrongTurn,
+    OwnPieceAtDestination,
+    InvalidMovement,
+    PathBlocked,
+    LeavesKingInCheck,
+    NoCastlingRights,
+    CastlingBlocked,
+    CastlingOutOfCheck,
+    CastlingThroughCheck,
+    PromotionRequired,
+    InvalidPromotion,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            MoveError::SameSpace => "piece is already there",
+            MoveError::NoPiece => "no piece to move",
+            MoveError::WrongTurn => "not that side's turn",
+            MoveError::OwnPieceAtDestination => "space is occupied by own piece",
+            MoveError::InvalidMovement => "piece can't move that way",
+            MoveError::PathBlocked => "path is blocked",
+            MoveError::LeavesKingInCheck => "king would be in check",
+            MoveError::NoCastlingRights => "king or rook has already moved",
+            MoveError::CastlingBlocked => "pieces between king and rook",
+            MoveError::CastlingOutOfCheck => "can't castle out of check",
+            MoveError::CastlingThroughCheck => "can't castle through check",
+            MoveError::PromotionRequired => "pawn must promote",
+            MoveError::InvalidPromotion => "can't promote that way",
+        };
+        write!(f, "{reason}")
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates() {
+        let mv = Move::from_coordinates("e2e4").unwrap();
+        assert_eq!(mv, Move::new(4, 1, 4, 3, None));
+        assert_eq!(mv.to_string(), "e2e4");
+        let mv = Move::from_coordinates("a7a8N").unwrap();
+        assert_eq!(mv, Move::new(0, 6, 0, 7, Some(PieceType::Knight)));
+        assert_eq!(mv.to_string(), "a7a8n");
+        assert_eq!(Move::from_coordinates("e2e9"), None);
+        assert_eq!(Move::from_coordinates("e7e8k"), None);
+        assert_eq!(Move::from_coordinates("Nf3"), None);
+        assert_eq!(Move::from_coordinates("e2e4e"), None);
+    }
+}
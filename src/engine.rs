@@ -0,0 +1,537 @@
+use crate::board::Board;
+use crate::chess_move::Move;
+use crate::color::Color;
+use crate::piece::{Piece, PieceType};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// scores within MAX_DEPTH of this are forced mates
+pub const MATE_SCORE: i32 = 100_000;
+pub const MAX_DEPTH: u8 = 64;
+// how long to think when given neither a depth nor a time
+pub const DEFAULT_MOVETIME: Duration = Duration::from_secs(1);
+// milliseconds kept back from the clock for communication delays
+pub const DEFAULT_MOVE_OVERHEAD: u64 = 50;
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+// a search stops at whichever limit it reaches first, and at MAX_DEPTH if neither is set
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    pub movetime: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u8) -> Self {
+        Self {
+            depth: Some(depth),
+            movetime: None,
+        }
+    }
+
+    pub fn movetime(movetime: Duration) -> Self {
+        Self {
+            depth: None,
+            movetime: Some(movetime),
+        }
+    }
+
+    // a movetime spending an even share of the time left plus most of the increment, all in
+    // milliseconds, keeping overhead back; moves_to_go is the moves left until the next time control
+    pub fn from_clock(time: u64, increment: u64, moves_to_go: Option<u64>, overhead: u64) -> Self {
+        let moves_to_go = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let budget = time / moves_to_go + increment * 3 / 4;
+        let budget = budget
+            .min(time.saturating_sub(overhead))
+            .saturating_sub(overhead)
+            .max(1);
+        Self::movetime(Duration::from_millis(budget))
+    }
+
+    // these limits, with DEFAULT_MOVETIME if they would otherwise search until stopped
+    pub fn or_default_movetime(self) -> Self {
+        Self {
+            movetime: self
+                .movetime
+                .or(self.depth.is_none().then_some(DEFAULT_MOVETIME)),
+            ..self
+        }
+    }
+}
+
+// the result of one completed iteration of iterative deepening
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: u8,
+    pub score: i32, // centipawns for the side to move
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: Vec<Move>, // best line found, starting with the move to play
+}
+
+impl SearchInfo {
+    pub fn best_move(&self) -> Option<Move> {
+        self.pv.first().copied()
+    }
+
+    // moves until mate, negative if the side to move is getting mated
+    pub fn mate_in(&self) -> Option<i32> {
+        let plies = MATE_SCORE - self.score.abs();
+        if plies > i32::from(MAX_DEPTH) {
+            return None;
+        }
+        let moves = (plies + 1) / 2;
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+}
+
+pub fn best_move(board: &Board, limits: SearchLimits) -> Option<Move> {
+    search(board, limits, &AtomicBool::new(false), |_| {}).and_then(|info| info.best_move())
+}
+
+// iterative deepening alpha-beta search, calling report after each completed depth
+// returns None if the side to move has no legal moves
+pub fn search(
+    board: &Board,
+    limits: SearchLimits,
+    stop: &AtomicBool,
+    mut report: impl FnMut(&SearchInfo),
+) -> Option<SearchInfo> {
+    let mut searcher = Searcher {
+        board: board.clone(),
+        start: Instant::now(),
+        movetime: limits.movetime,
+        stop,
+        nodes: 0,
+        aborted: false,
+    };
+    let mut root_moves = searcher.board.legal_moves();
+    if root_moves.is_empty() {
+        return None;
+    }
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    let mut best: Option<SearchInfo> = None;
+
+    for depth in 1..=max_depth {
+        // search the previous best line first so alpha-beta cuts off as much as possible
+        if let Some(mv) = best.as_ref().and_then(SearchInfo::best_move) {
+            let index = root_moves.iter().position(|&m| m == mv).unwrap();
+            root_moves[..=index].rotate_right(1);
+        } else {
+            searcher.order_moves(&mut root_moves);
+        }
+        let mut alpha = -MATE_SCORE - 1;
+        let mut pv = vec![];
+        for &mv in &root_moves {
+            searcher.board.make(mv).expect("legal moves can be made");
+            let mut line = vec![];
+            let score = -searcher.negamax(depth - 1, 1, -MATE_SCORE - 1, -alpha, &mut line);
+            searcher.board.unmake();
+            if searcher.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                pv = vec![mv];
+                pv.extend(line);
+            }
+        }
+        // an unfinished iteration is only used if there is nothing better
+        if searcher.aborted && best.is_some() {
+            break;
+        }
+        if pv.is_empty() {
+            pv.push(root_moves[0]);
+            alpha = evaluate(board);
+        }
+        let info = SearchInfo {
+            depth,
+            score: alpha,
+            nodes: searcher.nodes,
+            elapsed: searcher.start.elapsed(),
+            pv,
+        };
+        report(&info);
+        let found_mate = info.mate_in().is_some();
+        best = Some(info);
+        if searcher.aborted || found_mate {
+            break;
+        }
+    }
+    best
+}
+
+struct Searcher<'a> {
+    board: Board,
+    start: Instant,
+    movetime: Option<Duration>,
+    stop: &'a AtomicBool,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl Searcher<'_> {
+    fn should_stop(&mut self) -> bool {
+        if !self.aborted {
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || self
+                    .movetime
+                    .is_some_and(|movetime| self.start.elapsed() >= movetime);
+        }
+        self.aborted
+    }
+
+    // score of the position for the side to move, filling pv with the best line from here
+    fn negamax(
+        &mut self,
+        depth: u8,
+        ply: u8,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.should_stop() {
+            return 0;
+        }
+        if self.is_draw() {
+            return 0;
+        }
+        let mut moves = self.board.legal_moves();
+        if moves.is_empty() {
+            return if self.board.is_in_check(self.board.turn_color()) {
+                -MATE_SCORE + i32::from(ply)
+            } else {
+                0
+            };
+        }
+        if depth == 0 || ply >= MAX_DEPTH {
+            return self.quiescence(alpha, beta);
+        }
+
+        self.order_moves(&mut moves);
+        for mv in moves {
+            self.board.make(mv).expect("legal moves can be made");
+            let mut line = vec![];
+            let score = -self.negamax(depth - 1, ply + 1, -beta, -alpha, &mut line);
+            self.board.unmake();
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(mv);
+                pv.extend(line);
+            }
+        }
+        alpha
+    }
+
+    // only captures and promotions are searched, so the evaluation isn't taken mid-exchange
+    fn quiescence(&mut self, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        let stand_pat = evaluate(&self.board);
+        if stand_pat >= beta {
+            return beta;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut moves = self.board.legal_moves();
+        moves.retain(|&mv| self.is_capture(mv) || mv.promotion() == Some(PieceType::Queen));
+        self.order_moves(&mut moves);
+        for mv in moves {
+            self.board.make(mv).expect("legal moves can be made");
+            let score = -self.quiescence(-beta, -alpha);
+            self.board.unmake();
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    // repetitions count as draws as soon as they happen, since the side that can avoid them would
+    fn is_draw(&self) -> bool {
+        self.board.repetition_count() >= 2
+            || self.board.halfmove_clock() >= 100
+            || self.board.has_insufficient_material()
+    }
+
+    fn is_capture(&self, mv: Move) -> bool {
+        let (x1, _) = mv.origin();
+        let (x2, y2) = mv.dest();
+        self.board.space(x2, y2).piece().is_some()
+            || (x1 != x2 && self.piece_type_at(mv.origin()) == Some(PieceType::Pawn))
+    }
+
+    fn piece_type_at(&self, (x, y): (u8, u8)) -> Option<PieceType> {
+        self.board.space(x, y).piece().map(Piece::piece_type)
+    }
+
+    // most valuable victim, least valuable attacker first, then promotions
+    fn order_moves(&self, moves: &mut [Move]) {
+        moves.sort_by_cached_key(|&mv| {
+            let victim = if self.is_capture(mv) {
+                self.piece_type_at(mv.dest())
+                    .map_or(piece_value(PieceType::Pawn), piece_value)
+            } else {
+                0
+            };
+            let attacker = self.piece_type_at(mv.origin()).map_or(0, piece_value);
+            let promotion = mv.promotion().map_or(0, piece_value);
+            let score = if victim > 0 {
+                victim * 10 - attacker / 10
+            } else {
+                0
+            };
+            -(score + promotion)
+        });
+    }
+}
+
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 100,
+        PieceType::Knight => 320,
+        PieceType::Bishop => 330,
+        PieceType::Rook => 500,
+        PieceType::Queen => 900,
+        PieceType::King => 0,
+    }
+}
+
+// material and piece placement, in centipawns for the side to move
+pub fn evaluate(board: &Board) -> i32 {
+    let mut score = 0;
+    for (y, row) in (0u8..).zip(board.spaces()) {
+        for (x, space) in (0u8..).zip(row) {
+            let Some(piece) = space.piece() else {
+                continue;
+            };
+            // the tables are laid out from white's side, rank 8 first
+            let row = match piece.color() {
+                Color::White => 7 - y,
+                Color::Black => y,
+            };
+            let table = match piece.piece_type() {
+                PieceType::Pawn => &PAWN_TABLE,
+                PieceType::Knight => &KNIGHT_TABLE,
+                PieceType::Bishop => &BISHOP_TABLE,
+                PieceType::Rook => &ROOK_TABLE,
+                PieceType::Queen => &QUEEN_TABLE,
+                PieceType::King => &KING_TABLE,
+            };
+            let value = piece_value(piece.piece_type()) + table[row as usize][x as usize];
+            if piece.color() == board.turn_color() {
+                score += value;
+            } else {
+                score -= value;
+            }
+        }
+    }
+    score
+}
+
+const PAWN_TABLE: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [50, 50, 50, 50, 50, 50, 50, 50],
+    [10, 10, 20, 30, 30, 20, 10, 10],
+    [5, 5, 10, 25, 25, 10, 5, 5],
+    [0, 0, 0, 20, 20, 0, 0, 0],
+    [5, -5, -10, 0, 0, -10, -5, 5],
+    [5, 10, 10, -20, -20, 10, 10, 5],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+];
+
+const KNIGHT_TABLE: [[i32; 8]; 8] = [
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+    [-40, -20, 0, 0, 0, 0, -20, -40],
+    [-30, 0, 10, 15, 15, 10, 0, -30],
+    [-30, 5, 15, 20, 20, 15, 5, -30],
+    [-30, 0, 15, 20, 20, 15, 0, -30],
+    [-30, 5, 10, 15, 15, 10, 5, -30],
+    [-40, -20, 0, 5, 5, 0, -20, -40],
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+];
+
+const BISHOP_TABLE: [[i32; 8]; 8] = [
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+    [-10, 0, 0, 0, 0, 0, 0, -10],
+    [-10, 0, 5, 10, 10, 5, 0, -10],
+    [-10, 5, 5, 10, 10, 5, 5, -10],
+    [-10, 0, 10, 10, 10, 10, 0, -10],
+    [-10, 10, 10, 10, 10, 10, 10, -10],
+    [-10, 5, 0, 0, 0, 0, 5, -10],
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+];
+
+const ROOK_TABLE: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [5, 10, 10, 10, 10, 10, 10, 5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+    [0, 0, 0, 5, 5, 0, 0, 0],
+];
+
+const QUEEN_TABLE: [[i32; 8]; 8] = [
+    [-20, -10, -10, -5, -5, -10, -10, -20],
+    [-10, 0, 0, 0, 0, 0, 0, -10],
+    [-10, 0, 5, 5, 5, 5, 0, -10],
+    [-5, 0, 5, 5, 5, 5, 0, -5],
+    [0, 0, 5, 5, 5, 5, 0, -5],
+    [-10, 5, 5, 5, 5, 5, 0, -10],
+    [-10, 0, 5, 0, 0, 0, 0, -10],
+    [-20, -10, -10, -5, -5, -10, -10, -20],
+];
+
+const KING_TABLE: [[i32; 8]; 8] = [
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-20, -30, -30, -40, -40, -30, -30, -20],
+    [-10, -20, -20, -20, -20, -20, -20, -10],
+    [20, 20, 0, 0, 0, 0, 20, 20],
+    [20, 30, 10, 0, 0, 10, 30, 20],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starting_position_is_even() {
+        assert_eq!(evaluate(&Board::new()), 0);
+    }
+
+    #[test]
+    fn evaluation_is_from_side_to_move() {
+        // white is a queen up
+        let b = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert!(evaluate(&b) > 800);
+        let b = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+        assert!(evaluate(&b) < -800);
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        let b = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let info = search(&b, SearchLimits::depth(3), &AtomicBool::new(false), |_| {}).unwrap();
+        assert_eq!(info.best_move(), Some(Move::new(0, 0, 0, 7, None)));
+        assert_eq!(info.mate_in(), Some(1));
+    }
+
+    #[test]
+    fn finds_mate_in_two() {
+        // 1. Ra7 Kg8 2. Rb8#
+        let b = Board::from_fen("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1").unwrap();
+        let info = search(&b, SearchLimits::depth(4), &AtomicBool::new(false), |_| {}).unwrap();
+        assert_eq!(info.mate_in(), Some(2));
+    }
+
+    #[test]
+    fn takes_hanging_queen() {
+        let b = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            best_move(&b, SearchLimits::depth(2)),
+            Some(Move::new(3, 1, 3, 4, None))
+        );
+    }
+
+    #[test]
+    fn avoids_losing_queen() {
+        // Qxd7 wins a pawn but the queen is lost to the rook
+        let b = Board::from_fen("3rk3/3p4/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert_ne!(
+            best_move(&b, SearchLimits::depth(2)),
+            Some(Move::new(3, 0, 3, 6, None))
+        );
+    }
+
+    #[test]
+    fn reports_each_depth() {
+        let mut depths = vec![];
+        let info = search(
+            &Board::new(),
+            SearchLimits::depth(3),
+            &AtomicBool::new(false),
+            |info| depths.push(info.depth),
+        )
+        .unwrap();
+        assert_eq!(depths, vec![1, 2, 3]);
+        assert_eq!(info.depth, 3);
+        assert!(
+            Board::new()
+                .legal_moves()
+                .contains(&info.best_move().unwrap())
+        );
+    }
+
+    #[test]
+    fn stops_when_asked() {
+        let info = search(
+            &Board::new(),
+            SearchLimits::default(),
+            &AtomicBool::new(true),
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(info.depth, 1);
+        assert!(info.best_move().is_some());
+    }
+
+    #[test]
+    fn respects_movetime() {
+        let start = Instant::now();
+        let info = search(
+            &Board::new(),
+            SearchLimits::movetime(Duration::from_millis(100)),
+            &AtomicBool::new(false),
+            |_| {},
+        )
+        .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(info.best_move().is_some());
+    }
+
+    #[test]
+    fn no_moves_when_mated() {
+        let b = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(best_move(&b, SearchLimits::depth(2)), None);
+    }
+
+    #[test]
+    fn default_movetime_only_without_limits() {
+        assert_eq!(
+            SearchLimits::default().or_default_movetime(),
+            SearchLimits::movetime(DEFAULT_MOVETIME)
+        );
+        assert_eq!(
+            SearchLimits::depth(3).or_default_movetime(),
+            SearchLimits::depth(3)
+        );
+        let movetime = SearchLimits::movetime(Duration::from_millis(20));
+        assert_eq!(movetime.or_default_movetime(), movetime);
+    }
+
+    #[test]
+    fn clock_budget() {
+        let millis = |millis| SearchLimits::movetime(Duration::from_millis(millis));
+        assert_eq!(SearchLimits::from_clock(60_000, 0, None, 0), millis(2000));
+        assert_eq!(
+            SearchLimits::from_clock(60_000, 2000, Some(40), 50),
+            millis(2950)
+        );
+        // never more than the clock has left, and never nothing
+        assert_eq!(SearchLimits::from_clock(1000, 0, Some(1), 50), millis(900));
+        assert_eq!(SearchLimits::from_clock(10, 0, None, 50), millis(1));
+    }
+}
@@ -1,32 +1,73 @@
 use crate::board::Board;
+use crate::chess_move::{Move, MoveError};
 use crate::color::Color;
-use crate::piece::PieceType;
+use crate::engine::{self, SearchInfo, SearchLimits};
+use crate::game_result::{DrawReason, GameResult};
+use crate::pgn::{self, PgnTags};
+use crate::piece::{Piece, PieceType};
 use crate::space::Space;
+use crate::uci_client::UciEngine;
 use crossterm::{
     cursor,
-    event::{Event, KeyCode, read},
+    event::{self, Event, KeyCode, MouseButton, MouseEvent, MouseEventKind, read},
     execute, queue,
     style::{self, Color as TermColor, Stylize},
     terminal,
 };
 use std::collections::HashMap;
+use std::fs;
 use std::io::{Result, Stdout, Write, stdout};
+use std::sync::atomic::AtomicBool;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const SPACE_WIDTH: u16 = 5;
 const SPACE_HEIGHT: u16 = 3;
-const MIN_X: u16 = SPACE_WIDTH / 2;
+const BOARD_X: u16 = 2; // room for the rank labels
+const MIN_X: u16 = BOARD_X + SPACE_WIDTH / 2;
 const MAX_X: u16 = SPACE_WIDTH * 7 + MIN_X;
 const MIN_Y: u16 = SPACE_HEIGHT / 2;
 const MAX_Y: u16 = SPACE_HEIGHT * 7 + MIN_Y;
+const STATUS_WIDTH: usize = 60;
+const HISTORY_X: u16 = BOARD_X + SPACE_WIDTH * 8 + SPACE_WIDTH / 2 + 18;
+const HISTORY_ROWS: usize = SPACE_HEIGHT as usize * 8 - 1;
+const HISTORY_SAN_WIDTH: usize = 8;
+
+// which side's home rank is drawn at the bottom of the screen
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Orientation {
+    WhiteAtBottom,
+    BlackAtBottom,
+    SideToMove, // flips after every move for hot-seat play
+}
+
+// what the space bar or a click on a space does, given the current selection
+#[derive(Debug, PartialEq, Eq)]
+enum Activation {
+    Deselect,
+    Select,
+    Move(Move),
+    Nothing,
+}
 
 pub struct Game {
     board: Board,
     selected: Option<(u8, u8)>,
+    destinations: Vec<(u8, u8)>, // legal destinations of the selected piece
+    dragging: Option<(u8, u8)>,  // where the mouse button was pressed
     undoing: bool,
     quitting: bool,
-    promoting: Option<(u8, u8)>,
+    claiming_draw: bool,
+    promoting: Option<Move>,  // a pawn move waiting for its promotion piece
+    entering: Option<String>, // a move being typed at the prompt
+    engine: Option<Color>,    // the side played by the computer
+    limits: SearchLimits,     // how long the computer thinks
+    uci_engine: Option<UciEngine>, // plays and analyses instead of the built-in engine
+    orientation: Orientation,
+    message: Option<String>,
     stdout: Stdout,
-    victor: Option<Color>,
+    result: Option<GameResult>,
+    history_top: usize, // first move list row shown
+    history_ply: usize, // ply count when the move list was last drawn
 }
 
 impl Game {
@@ -34,23 +75,46 @@ impl Game {
         Self {
             board: Board::new(),
             selected: None,
+            destinations: vec![],
+            dragging: None,
             undoing: false,
             quitting: false,
+            claiming_draw: false,
             promoting: None,
+            entering: None,
+            engine: None,
+            limits: SearchLimits::default(),
+            uci_engine: None,
+            orientation: Orientation::WhiteAtBottom,
+            message: None,
             stdout: stdout(),
-            victor: None,
+            result: None,
+            history_top: 0,
+            history_ply: 0,
         }
     }
 
     pub fn with_board(board: Board) -> Self {
+        let result = board.result();
         Self {
             board,
             selected: None,
+            destinations: vec![],
+            dragging: None,
             undoing: false,
             quitting: false,
+            claiming_draw: false,
             promoting: None,
+            entering: None,
+            engine: None,
+            limits: SearchLimits::default(),
+            uci_engine: None,
+            orientation: Orientation::WhiteAtBottom,
+            message: None,
             stdout: stdout(),
-            victor: None,
+            result,
+            history_top: 0,
+            history_ply: 0,
         }
     }
 
@@ -58,115 +122,230 @@ impl Game {
         queue!(self.stdout, terminal::Clear(terminal::ClearType::All))?;
         self.queue_board()?;
         self.queue_captured_pieces()?;
+        self.queue_move_history()?;
         queue!(
             self.stdout,
             cursor::MoveLeft(1),
             cursor::SetCursorStyle::BlinkingBlock,
             cursor::Show,
-            cursor::MoveTo(SPACE_WIDTH / 2, SPACE_HEIGHT * 7 + SPACE_HEIGHT / 2),
+            cursor::MoveTo(MIN_X, MAX_Y),
         )?;
         self.queue_status_text()?;
         self.stdout.flush()?;
         terminal::enable_raw_mode()?;
+        execute!(self.stdout, event::EnableMouseCapture)?;
 
         loop {
+            if self.is_engine_turn() {
+                self.play_engine_move()?;
+                continue;
+            }
             let e = read()?;
-            if let Event::Key(k) = e {
+            if let Event::Mouse(m) = e {
+                self.handle_mouse(m)?;
+            } else if let Event::Key(k) = e {
                 let pos = cursor::position()?;
-                let can_move = self.promoting.is_none() && self.victor.is_none();
-                match k.code {
-                    KeyCode::Up => {
-                        if can_move && pos.1 > MIN_Y {
-                            execute!(self.stdout, cursor::MoveUp(SPACE_HEIGHT))?;
+                let cursor_space = self.screen_space(pos);
+                // messages only last until the next key press
+                if self.message.take().is_some() {
+                    self.queue_status_text()?;
+                    self.stdout.flush()?;
+                }
+                let can_move = self.promoting.is_none() && self.result.is_none();
+                // the move prompt takes every key until it is submitted or cancelled
+                if let Some(text) = &mut self.entering {
+                    match k.code {
+                        KeyCode::Char(c) => text.push(c),
+                        KeyCode::Backspace => {
+                            text.pop();
                         }
-                    }
-                    KeyCode::Down => {
-                        if can_move && pos.1 < MAX_Y {
-                            execute!(self.stdout, cursor::MoveDown(SPACE_HEIGHT))?;
+                        KeyCode::Enter => {
+                            let text = self.entering.take().unwrap();
+                            self.enter_move(&text)?;
+                            continue;
                         }
+                        KeyCode::Esc => self.entering = None,
+                        _ => {}
                     }
-                    KeyCode::Left => {
-                        if can_move && pos.0 > MIN_X {
-                            execute!(self.stdout, cursor::MoveLeft(SPACE_WIDTH))?;
-                        }
+                    self.queue_status_text()?;
+                    self.stdout.flush()?;
+                    continue;
+                }
+                match k.code {
+                    KeyCode::Up if can_move && pos.1 > MIN_Y => {
+                        execute!(self.stdout, cursor::MoveUp(SPACE_HEIGHT))?;
                     }
-                    KeyCode::Right => {
-                        if can_move && pos.0 < MAX_X {
-                            execute!(self.stdout, cursor::MoveRight(SPACE_WIDTH))?;
-                        }
+                    KeyCode::Down if can_move && pos.1 < MAX_Y => {
+                        execute!(self.stdout, cursor::MoveDown(SPACE_HEIGHT))?;
+                    }
+                    KeyCode::Left if can_move && pos.0 > MIN_X => {
+                        execute!(self.stdout, cursor::MoveLeft(SPACE_WIDTH))?;
+                    }
+                    KeyCode::Right if can_move && pos.0 < MAX_X => {
+                        execute!(self.stdout, cursor::MoveRight(SPACE_WIDTH))?;
                     }
                     // promote to bishop
-                    KeyCode::Char('b') => {
-                        if let Some(promoting) = self.promoting {
-                            self.board
-                                .promote_pawn(promoting.0, promoting.1, PieceType::Bishop);
-                            self.promoting = None;
-                            self.check_victor();
-                            self.queue_board()?;
-                            self.queue_status_text()?;
-                            queue!(self.stdout, cursor::MoveTo(pos.0, pos.1))?;
-                            self.stdout.flush()?;
-                        }
+                    KeyCode::Char('b') if self.promoting.is_some() => {
+                        self.promote(PieceType::Bishop)?;
                     }
                     // promote to rook
-                    KeyCode::Char('r') => {
-                        if let Some(promoting) = self.promoting {
-                            self.board
-                                .promote_pawn(promoting.0, promoting.1, PieceType::Rook);
-                            self.promoting = None;
-                            self.check_victor();
-                            self.queue_board()?;
-                            self.queue_status_text()?;
-                            queue!(self.stdout, cursor::MoveTo(pos.0, pos.1))?;
-                            self.stdout.flush()?;
-                        }
+                    KeyCode::Char('r') if self.promoting.is_some() => {
+                        self.promote(PieceType::Rook)?;
                     }
                     // prompt to undo
                     KeyCode::Char('z' | 'u') => {
                         self.undoing = true;
                         self.quitting = false;
+                        self.claiming_draw = false;
                         self.queue_status_text()?;
                         self.stdout.flush()?;
                     }
+                    // redo the last undone move
+                    KeyCode::Char('r') if can_move => {
+                        self.quitting = false;
+                        self.undoing = false;
+                        self.claiming_draw = false;
+                        if self.board.redo_move() {
+                            self.selected = None;
+                            self.check_result();
+                            self.queue_board()?;
+                            self.queue_captured_pieces()?;
+                            self.queue_move_history()?;
+                        } else {
+                            self.message = Some("NOTHING TO REDO".to_string());
+                        }
+                        self.queue_status_text()?;
+                        self.queue_cursor_back(pos, cursor_space)?;
+                        self.stdout.flush()?;
+                    }
                     // prompt to quit or promote to queen
                     KeyCode::Char('q') => {
-                        if let Some(promoting) = self.promoting {
-                            self.board
-                                .promote_pawn(promoting.0, promoting.1, PieceType::Queen);
-                            self.promoting = None;
-                            self.check_victor();
-                            self.queue_board()?;
-                            self.queue_status_text()?;
-                            queue!(self.stdout, cursor::MoveTo(pos.0, pos.1))?;
-                            self.stdout.flush()?;
+                        if self.promoting.is_some() {
+                            self.promote(PieceType::Queen)?;
                         } else {
                             self.quitting = true;
                             self.undoing = false;
+                            self.claiming_draw = false;
                             self.queue_status_text()?;
                             self.stdout.flush()?;
                         }
                     }
-                    // confirm quit or undo
+                    // show the computer's best move
+                    KeyCode::Char('h') if can_move => {
+                        self.quitting = false;
+                        self.undoing = false;
+                        self.claiming_draw = false;
+                        self.analyse()?;
+                    }
+                    // open the move prompt
+                    KeyCode::Char(':' | '/') if can_move => {
+                        self.entering = Some(String::new());
+                        self.quitting = false;
+                        self.undoing = false;
+                        self.claiming_draw = false;
+                        self.queue_status_text()?;
+                        self.stdout.flush()?;
+                    }
+                    // flip the board
+                    KeyCode::Char('f') => {
+                        self.orientation = if self.is_flipped() {
+                            Orientation::WhiteAtBottom
+                        } else {
+                            Orientation::BlackAtBottom
+                        };
+                        self.queue_board()?;
+                        self.queue_captured_pieces()?;
+                        self.queue_cursor_back(pos, cursor_space)?;
+                        self.stdout.flush()?;
+                    }
+                    // toggle orienting the board to the side to move
+                    KeyCode::Char('a') => {
+                        self.orientation = match (self.orientation, self.is_flipped()) {
+                            (Orientation::SideToMove, false) => Orientation::WhiteAtBottom,
+                            (Orientation::SideToMove, true) => Orientation::BlackAtBottom,
+                            _ => Orientation::SideToMove,
+                        };
+                        self.message = Some(
+                            if self.orientation == Orientation::SideToMove {
+                                "AUTO-FLIP ON"
+                            } else {
+                                "AUTO-FLIP OFF"
+                            }
+                            .to_string(),
+                        );
+                        self.queue_board()?;
+                        self.queue_captured_pieces()?;
+                        self.queue_status_text()?;
+                        self.queue_cursor_back(pos, cursor_space)?;
+                        self.stdout.flush()?;
+                    }
+                    // scroll the move list
+                    KeyCode::PageUp => {
+                        self.history_top = self.history_top.saturating_sub(HISTORY_ROWS);
+                        self.queue_move_history()?;
+                        self.stdout.flush()?;
+                    }
+                    KeyCode::PageDown => {
+                        self.history_top += HISTORY_ROWS;
+                        self.queue_move_history()?;
+                        self.stdout.flush()?;
+                    }
+                    // save the game as PGN
+                    KeyCode::Char('s') => {
+                        self.message = Some(match self.save_pgn() {
+                            Ok(path) => format!("SAVED {path}"),
+                            Err(e) => format!("SAVE FAILED: {e}"),
+                        });
+                        self.queue_status_text()?;
+                        self.stdout.flush()?;
+                    }
+                    // prompt to claim a draw
+                    KeyCode::Char('d') if can_move && self.board.claimable_draw().is_some() => {
+                        self.claiming_draw = true;
+                        self.undoing = false;
+                        self.quitting = false;
+                        self.queue_status_text()?;
+                        self.stdout.flush()?;
+                    }
+                    // confirm quit, undo, or draw claim
                     KeyCode::Char('y') => {
+                        if self.claiming_draw {
+                            self.claiming_draw = false;
+                            self.selected = None;
+                            self.result = self.board.claimable_draw().map(GameResult::Draw);
+                            self.queue_board()?;
+                            self.queue_status_text()?;
+                            queue!(self.stdout, cursor::MoveTo(pos.0, pos.1))?;
+                            self.stdout.flush()?;
+                        }
                         if self.undoing {
                             self.selected = None;
                             self.promoting = None;
-                            self.victor = None;
+                            self.result = None;
                             self.board.undo_last_move();
+                            // take back the computer's reply along with the player's move
+                            if self.is_engine_turn() {
+                                self.board.undo_last_move();
+                            }
                             self.undoing = false;
                             self.queue_board()?;
                             self.queue_captured_pieces()?;
+                            self.queue_move_history()?;
                             self.queue_status_text()?;
-                            queue!(self.stdout, cursor::MoveTo(pos.0, pos.1))?;
+                            self.queue_cursor_back(pos, cursor_space)?;
                             self.stdout.flush()?;
                         }
                         if self.quitting {
                             break;
                         }
                     }
-                    // stop undoing/quitting, or promote to knight
+                    // stop undoing/quitting/claiming, or promote to knight
                     KeyCode::Char('n') => {
-                        if self.undoing {
+                        if self.claiming_draw {
+                            self.claiming_draw = false;
+                            self.queue_status_text()?;
+                            self.stdout.flush()?;
+                        } else if self.undoing {
                             self.undoing = false;
                             self.queue_status_text()?;
                             self.stdout.flush()?;
@@ -174,19 +353,16 @@ impl Game {
                             self.quitting = false;
                             self.queue_status_text()?;
                             self.stdout.flush()?;
-                        } else if let Some(promoting) = self.promoting {
-                            self.board
-                                .promote_pawn(promoting.0, promoting.1, PieceType::Knight);
-                            self.promoting = None;
-                            self.check_victor();
-                            self.queue_board()?;
-                            self.queue_status_text()?;
-                            queue!(self.stdout, cursor::MoveTo(pos.0, pos.1))?;
-                            self.stdout.flush()?;
+                        } else if self.promoting.is_some() {
+                            self.promote(PieceType::Knight)?;
                         }
                     }
-                    // deselect or stop undoing/quitting
+                    // deselect, cancel promotion, or stop undoing/quitting/claiming
                     KeyCode::Esc => {
+                        if self.promoting.take().is_some() {
+                            self.queue_status_text()?;
+                            self.stdout.flush()?;
+                        }
                         if self.selected.is_some() {
                             self.selected = None;
                             self.queue_board()?;
@@ -203,74 +379,16 @@ impl Game {
                             self.queue_status_text()?;
                             self.stdout.flush()?;
                         }
+                        if self.claiming_draw {
+                            self.claiming_draw = false;
+                            self.queue_status_text()?;
+                            self.stdout.flush()?;
+                        }
                     }
                     // select or move piece
                     KeyCode::Char(' ') => {
-                        if !can_move {
-                            continue;
-                        }
-                        self.quitting = false;
-                        self.undoing = false;
-                        if pos.0 > MAX_X || pos.1 > MAX_Y {
-                            continue;
-                        }
-                        #[allow(clippy::cast_possible_truncation)]
-                        let x = (pos.0 / SPACE_WIDTH) as u8;
-                        #[allow(clippy::cast_possible_truncation)]
-                        let y = 7 - (pos.1 / SPACE_HEIGHT) as u8;
-                        if let Some(s) = self.selected {
-                            if s.0 == x && s.1 == y {
-                                let space = self.board.space(x, y);
-                                let colors = get_term_colors(space);
-                                execute!(
-                                    self.stdout,
-                                    style::PrintStyledContent(
-                                        space.draw().with(colors.0).on_black()
-                                    ),
-                                    cursor::MoveLeft(1)
-                                )?;
-                                self.selected = None;
-                            } else if x < 8 && y < 8 && self.board.move_piece(s.0, s.1, x, y) {
-                                self.selected = None;
-                                self.promoting = {
-                                    let piece = self.board.space(x, y).piece().unwrap();
-                                    if piece.piece_type() == PieceType::Pawn
-                                        && ((piece.color() == Color::White && y == 7)
-                                            || (piece.color() == Color::Black && y == 0))
-                                    {
-                                        Some((x, y))
-                                    } else {
-                                        None
-                                    }
-                                };
-
-                                self.check_victor();
-                                self.queue_board()?;
-                                self.queue_captured_pieces()?;
-                                queue!(
-                                    self.stdout,
-                                    cursor::MoveTo(
-                                        u16::from(x) * SPACE_WIDTH + (SPACE_WIDTH / 2),
-                                        (7 - u16::from(y)) * SPACE_HEIGHT + (SPACE_HEIGHT / 2)
-                                    )
-                                )?;
-                                self.queue_status_text()?;
-                                self.stdout.flush()?;
-                            }
-                        } else if x < 8 && y < 8 {
-                            let space = self.board.space(x, y);
-                            if let Some(piece_color) = space.piece_color() {
-                                if piece_color == self.board.turn_color() {
-                                    self.selected = Some((x, y));
-                                    self.queue_space(x, y)?;
-                                    queue!(
-                                        self.stdout,
-                                        cursor::MoveUp(MIN_Y),
-                                        cursor::MoveLeft(MIN_X + 1)
-                                    )?;
-                                    self.stdout.flush()?;
-                                }
-                            }
+                        if let Some((x, y)) = cursor_space {
+                            self.activate_space(x, y)?;
                         }
                     }
                     _ => {}
@@ -278,19 +396,305 @@ impl Game {
             }
         }
 
+        execute!(self.stdout, event::DisableMouseCapture)?;
         terminal::disable_raw_mode()?;
         execute!(self.stdout, cursor::MoveTo(0, 0))?;
 
         Ok(())
     }
 
-    fn check_victor(&mut self) {
-        if self.board.is_in_checkmate(self.board.turn_color()) {
-            self.victor = Some(match self.board.turn_color() {
-                Color::Black => Color::White,
-                Color::White => Color::Black,
-            });
+    // has the computer play color
+    pub fn set_engine(&mut self, color: Color) {
+        self.engine = Some(color);
+    }
+
+    // limits each search by the computer, whether playing or analysing
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    // searches with an external engine rather than the built-in one
+    pub fn set_uci_engine(&mut self, uci_engine: UciEngine) {
+        self.uci_engine = Some(uci_engine);
+    }
+
+    fn is_engine_turn(&self) -> bool {
+        self.engine == Some(self.board.turn_color())
+            && self.result.is_none()
+            && self.promoting.is_none()
+    }
+
+    fn play_engine_move(&mut self) -> Result<()> {
+        self.message = Some("THINKING...".to_string());
+        self.queue_status_text()?;
+        self.stdout.flush()?;
+        match self.engine_move() {
+            Ok(mv) => {
+                self.message = None;
+                if !self.play_move(mv)? {
+                    self.promoting = None;
+                    self.engine = None;
+                    self.message = Some(format!("ENGINE ERROR: ILLEGAL MOVE {mv}").to_uppercase());
+                }
+            }
+            Err(e) => self.message = Some(format!("ENGINE ERROR: {e}").to_uppercase()),
         }
+        self.queue_status_text()?;
+        self.stdout.flush()
+    }
+
+    // the computer's move for the side to move, giving the side back to the player if it can't
+    // provide one rather than asking it again
+    fn engine_move(&mut self) -> std::result::Result<Move, String> {
+        let mv = match self.search() {
+            Ok(info) => info
+                .and_then(|info| info.best_move())
+                .ok_or_else(|| "no move".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if mv.is_err() {
+            self.engine = None;
+        }
+        mv
+    }
+
+    // shows the computer's choice for the side to move without playing it
+    fn analyse(&mut self) -> Result<()> {
+        self.message = Some("THINKING...".to_string());
+        self.queue_status_text()?;
+        self.stdout.flush()?;
+        self.message = Some(match self.search() {
+            Ok(Some(info)) => {
+                let san = self
+                    .board
+                    .move_to_san(info.best_move().expect("searches return a move"));
+                let score = match info.mate_in() {
+                    Some(moves) => format!("MATE {moves}"),
+                    None => format!("{:+.2}", f64::from(info.score) / 100.0),
+                };
+                format!("BEST: {san} ({score}, DEPTH {})", info.depth)
+            }
+            Ok(None) => "NO LEGAL MOVES".to_string(),
+            Err(e) => format!("ENGINE ERROR: {e}").to_uppercase(),
+        });
+        self.queue_status_text()?;
+        self.stdout.flush()
+    }
+
+    fn search(&mut self) -> Result<Option<SearchInfo>> {
+        if let Some(uci_engine) = &mut self.uci_engine {
+            uci_engine.search(&self.board, self.limits)
+        } else {
+            Ok(engine::search(
+                &self.board,
+                self.limits,
+                &AtomicBool::new(false),
+                |_| {},
+            ))
+        }
+    }
+
+    // selects, deselects, or moves the selected piece to the space, for the space bar and mouse clicks
+    fn activate_space(&mut self, x: u8, y: u8) -> Result<()> {
+        if self.promoting.is_some() || self.result.is_some() {
+            return Ok(());
+        }
+        self.quitting = false;
+        self.undoing = false;
+        self.claiming_draw = false;
+        match self.activation(x, y) {
+            Activation::Deselect => {
+                self.selected = None;
+                self.queue_board()?;
+                self.queue_cursor_to(x, y)?;
+            }
+            Activation::Select => {
+                self.selected = Some((x, y));
+                self.destinations = self
+                    .board
+                    .legal_moves_from(x, y)
+                    .into_iter()
+                    .map(Move::dest)
+                    .collect();
+                self.queue_board()?;
+                self.queue_cursor_to(x, y)?;
+            }
+            Activation::Move(mv) => {
+                self.play_move(mv)?;
+            }
+            Activation::Nothing => {}
+        }
+        self.queue_status_text()?;
+        self.stdout.flush()
+    }
+
+    fn activation(&self, x: u8, y: u8) -> Activation {
+        let own_piece = self.board.space(x, y).piece_color() == Some(self.board.turn_color());
+        match self.selected {
+            Some(s) if s == (x, y) => Activation::Deselect,
+            // picking another of the player's pieces switches to it rather than moving onto it
+            _ if own_piece => Activation::Select,
+            Some(s) => Activation::Move(Move::new(s.0, s.1, x, y, None)),
+            None => Activation::Nothing,
+        }
+    }
+
+    // clicking a space works like the space bar there, and dropping a dragged piece on another space moves it
+    fn handle_mouse(&mut self, event: MouseEvent) -> Result<()> {
+        let space = self.screen_space((event.column, event.row));
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // messages only last until the next click
+                if self.message.take().is_some() {
+                    self.queue_status_text()?;
+                }
+                self.dragging = space;
+                if let Some((x, y)) = space {
+                    self.queue_cursor_to(x, y)?;
+                    self.activate_space(x, y)?;
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let from = self.dragging.take();
+                if let Some((x, y)) = space
+                    && from.is_some()
+                    && from != space
+                    && self.selected == from
+                {
+                    self.queue_cursor_to(x, y)?;
+                    self.activate_space(x, y)?;
+                }
+            }
+            _ => {}
+        }
+        self.stdout.flush()
+    }
+
+    // whether the board is drawn with black's home rank at the bottom
+    fn is_flipped(&self) -> bool {
+        match self.orientation {
+            Orientation::WhiteAtBottom => false,
+            Orientation::BlackAtBottom => true,
+            Orientation::SideToMove => self.board.turn_color() == Color::Black,
+        }
+    }
+
+    // the top left corner of a space on screen
+    fn space_screen_pos(&self, x: u8, y: u8) -> (u16, u16) {
+        let (col, row) = if self.is_flipped() {
+            (7 - x, y)
+        } else {
+            (x, 7 - y)
+        };
+        (
+            BOARD_X + u16::from(col) * SPACE_WIDTH,
+            u16::from(row) * SPACE_HEIGHT,
+        )
+    }
+
+    // the space under a screen position, if it is on the board
+    fn screen_space(&self, pos: (u16, u16)) -> Option<(u8, u8)> {
+        let x = pos.0.checked_sub(BOARD_X)?;
+        if x >= SPACE_WIDTH * 8 || pos.1 >= SPACE_HEIGHT * 8 {
+            return None;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let (col, row) = ((x / SPACE_WIDTH) as u8, (pos.1 / SPACE_HEIGHT) as u8);
+        Some(if self.is_flipped() {
+            (7 - col, row)
+        } else {
+            (col, 7 - row)
+        })
+    }
+
+    fn queue_cursor_to(&mut self, x: u8, y: u8) -> Result<()> {
+        let (col, row) = self.space_screen_pos(x, y);
+        queue!(
+            self.stdout,
+            cursor::MoveTo(col + SPACE_WIDTH / 2, row + SPACE_HEIGHT / 2)
+        )
+    }
+
+    // puts the cursor back on the space it was on, which moves on screen if the board flipped
+    fn queue_cursor_back(&mut self, pos: (u16, u16), space: Option<(u8, u8)>) -> Result<()> {
+        if let Some((x, y)) = space {
+            self.queue_cursor_to(x, y)
+        } else {
+            queue!(self.stdout, cursor::MoveTo(pos.0, pos.1))
+        }
+    }
+
+    fn check_result(&mut self) {
+        self.result = self.board.result();
+    }
+
+    // makes the pending promotion move with the chosen piece
+    fn promote(&mut self, piece_type: PieceType) -> Result<()> {
+        let Some(pending) = self.promoting.take() else {
+            return Ok(());
+        };
+        let (x1, y1) = pending.origin();
+        let (x2, y2) = pending.dest();
+        self.play_move(Move::new(x1, y1, x2, y2, Some(piece_type)))?;
+        self.queue_status_text()?;
+        self.stdout.flush()
+    }
+
+    // makes the move and redraws, or waits for a promotion piece, or reports why it is illegal,
+    // returning whether the move was made
+    fn play_move(&mut self, mv: Move) -> Result<bool> {
+        match self.board.make_move(mv) {
+            Err(MoveError::PromotionRequired) => {
+                self.promoting = Some(mv);
+                Ok(false)
+            }
+            Ok(_) => {
+                self.selected = None;
+                self.check_result();
+                self.queue_board()?;
+                self.queue_captured_pieces()?;
+                self.queue_move_history()?;
+                let (x, y) = mv.dest();
+                self.queue_cursor_to(x, y)?;
+                Ok(true)
+            }
+            Err(e) => {
+                self.message = Some(format!("ILLEGAL MOVE: {e}").to_uppercase());
+                Ok(false)
+            }
+        }
+    }
+
+    // applies a move typed in coordinate notation (e2e4, e7e8q) or SAN (Nf3, O-O)
+    fn enter_move(&mut self, text: &str) -> Result<()> {
+        let text = text.trim();
+        if let Some(mv) = Move::from_coordinates(text) {
+            self.play_move(mv)?;
+        } else {
+            match self.board.parse_san(text) {
+                Ok(mv) => {
+                    self.play_move(mv)?;
+                }
+                Err(e) => self.message = Some(e.to_string().to_uppercase()),
+            }
+        }
+        self.queue_status_text()?;
+        self.stdout.flush()
+    }
+
+    // writes the game to a new file in the working directory, returning its name
+    fn save_pgn(&self) -> Result<String> {
+        let (year, month, day) = pgn::today();
+        let tags = PgnTags {
+            date: format!("{year:04}.{month:02}.{day:02}"),
+            ..PgnTags::default()
+        };
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let path = format!("chess-{secs}.pgn");
+        fs::write(&path, pgn::export_pgn(&self.board, &tags, self.result))?;
+        Ok(path)
     }
 
     fn queue_board(&mut self) -> Result<()> {
@@ -300,6 +704,24 @@ impl Game {
             }
         }
 
+        // rank labels on the left and file labels underneath, following the orientation
+        queue!(
+            self.stdout,
+            style::SetForegroundColor(TermColor::White),
+            style::SetBackgroundColor(TermColor::Black),
+        )?;
+        for i in 0u8..8u8 {
+            let (x, y) = self.space_screen_pos(i, i);
+            queue!(
+                self.stdout,
+                cursor::MoveTo(0, y + SPACE_HEIGHT / 2),
+                style::Print(char::from(b'1' + i)),
+                cursor::MoveTo(x + SPACE_WIDTH / 2, SPACE_HEIGHT * 8),
+                style::Print(char::from(b'a' + i)),
+            )?;
+        }
+        queue!(self.stdout, style::ResetColor)?;
+
         Ok(())
     }
 
@@ -310,9 +732,36 @@ impl Game {
         } else {
             false
         };
-        let x = u16::from(space_x) * SPACE_WIDTH;
-        let y = (7 - u16::from(space_y)) * SPACE_HEIGHT;
-        let (fg_color, bg_color) = get_term_colors(space);
+        let (x, y) = self.space_screen_pos(space_x, space_y);
+        let (fg_color, mut bg_color) = get_term_colors(space);
+        // tint the last move, a king in check, then the selected piece's legal destinations
+        if let Some(last_move) = self.board.moves().last()
+            && (last_move.origin() == (space_x, space_y) || last_move.dest() == (space_x, space_y))
+        {
+            bg_color = TermColor::DarkBlue;
+        }
+        if let Some(piece) = space.piece()
+            && piece.piece_type() == PieceType::King
+            && self.board.is_in_check(piece.color())
+        {
+            bg_color = TermColor::DarkRed;
+        }
+        if let Some(selected) = self.selected
+            && self.destinations.contains(&(space_x, space_y))
+        {
+            let en_passant = self.board.en_passant_target() == Some((space_x, space_y))
+                && self
+                    .board
+                    .space(selected.0, selected.1)
+                    .piece()
+                    .map(Piece::piece_type)
+                    == Some(PieceType::Pawn);
+            bg_color = if space.piece().is_some() || en_passant {
+                TermColor::DarkYellow
+            } else {
+                TermColor::DarkCyan
+            };
+        }
         queue!(
             self.stdout,
             cursor::MoveTo(x, y),
@@ -349,17 +798,48 @@ impl Game {
     fn queue_status_text(&mut self) -> Result<()> {
         let pos = cursor::position()?;
 
-        let (status, color) = if self.quitting {
+        let prompt;
+        let (status, color) = if let Some(message) = &self.message {
+            (message.as_str(), TermColor::Magenta)
+        } else if let Some(text) = &self.entering {
+            prompt = format!("MOVE: {text}_");
+            (prompt.as_str(), TermColor::Cyan)
+        } else if self.quitting {
             ("QUIT? (y/n)                ", TermColor::Magenta)
         } else if self.undoing {
             ("UNDO? (y/n)                ", TermColor::Magenta)
-        } else if self.victor.is_some() {
-            match self.victor.unwrap() {
-                Color::White => ("WHITE WINS!                 ", TermColor::Magenta),
-                Color::Black => ("BLACK WINS!                 ", TermColor::Magenta),
+        } else if self.claiming_draw {
+            ("CLAIM DRAW? (y/n)          ", TermColor::Magenta)
+        } else if let Some(result) = self.result {
+            match result {
+                GameResult::WhiteWins => ("WHITE WINS!                ", TermColor::Magenta),
+                GameResult::BlackWins => ("BLACK WINS!                ", TermColor::Magenta),
+                GameResult::Draw(DrawReason::Stalemate) => {
+                    ("DRAW: STALEMATE            ", TermColor::Magenta)
+                }
+                GameResult::Draw(DrawReason::FiftyMoveRule) => {
+                    ("DRAW: FIFTY-MOVE RULE      ", TermColor::Magenta)
+                }
+                GameResult::Draw(DrawReason::SeventyFiveMoveRule) => {
+                    ("DRAW: 75-MOVE RULE         ", TermColor::Magenta)
+                }
+                GameResult::Draw(DrawReason::ThreefoldRepetition) => {
+                    ("DRAW: THREEFOLD REPETITION ", TermColor::Magenta)
+                }
+                GameResult::Draw(DrawReason::FivefoldRepetition) => {
+                    ("DRAW: FIVEFOLD REPETITION  ", TermColor::Magenta)
+                }
+                GameResult::Draw(DrawReason::InsufficientMaterial) => {
+                    ("DRAW: INSUFFICIENT MATERIAL", TermColor::Magenta)
+                }
             }
         } else if self.promoting.is_some() {
             ("SELECT PROMOTION: (q/r/b/n)", TermColor::Magenta)
+        } else if self.board.claimable_draw().is_some() {
+            match self.board.turn_color() {
+                Color::White => ("WHITE (d: CLAIM DRAW)      ", TermColor::Green),
+                Color::Black => ("BLACK (d: CLAIM DRAW)      ", TermColor::Red),
+            }
         } else {
             match self.board.turn_color() {
                 Color::White => ("WHITE                      ", TermColor::Green),
@@ -370,7 +850,7 @@ impl Game {
         queue!(
             self.stdout,
             cursor::MoveTo(1, SPACE_HEIGHT * 8 + 1),
-            style::PrintStyledContent(status.with(color).on_black()),
+            style::PrintStyledContent(format!("{status:<STATUS_WIDTH$}").with(color).on_black()),
             cursor::MoveTo(pos.0, pos.1),
         )?;
 
@@ -380,15 +860,29 @@ impl Game {
     fn queue_captured_pieces(&mut self) -> Result<()> {
         let pos = cursor::position()?;
 
-        let white = self.board.captured_by_white().clone();
-        let black = self.board.captured_by_black().clone();
+        // pieces captured by the side at the top are listed at the top, in the captured pieces' color
+        let (top, top_color, bottom, bottom_color) = if self.is_flipped() {
+            (
+                self.board.captured_by_white().clone(),
+                TermColor::Red,
+                self.board.captured_by_black().clone(),
+                TermColor::Green,
+            )
+        } else {
+            (
+                self.board.captured_by_black().clone(),
+                TermColor::Green,
+                self.board.captured_by_white().clone(),
+                TermColor::Red,
+            )
+        };
 
-        let x_start = SPACE_WIDTH * 8 + SPACE_WIDTH / 2;
+        let x_start = BOARD_X + SPACE_WIDTH * 8 + SPACE_WIDTH / 2;
 
         queue!(
             self.stdout,
             cursor::MoveTo(x_start, 1),
-            style::SetForegroundColor(TermColor::Green),
+            style::SetForegroundColor(top_color),
             style::SetBackgroundColor(TermColor::Black),
         )?;
 
@@ -403,19 +897,19 @@ impl Game {
         }
         queue!(self.stdout, cursor::MoveTo(x_start, 1))?;
 
-        self.queue_captured_row(&black, PieceType::Queen, false)?;
-        self.queue_captured_row(&black, PieceType::Rook, false)?;
-        self.queue_captured_row(&black, PieceType::Bishop, false)?;
-        self.queue_captured_row(&black, PieceType::Knight, false)?;
-        self.queue_captured_row(&black, PieceType::Pawn, false)?;
+        self.queue_captured_row(&top, PieceType::Queen, false)?;
+        self.queue_captured_row(&top, PieceType::Rook, false)?;
+        self.queue_captured_row(&top, PieceType::Bishop, false)?;
+        self.queue_captured_row(&top, PieceType::Knight, false)?;
+        self.queue_captured_row(&top, PieceType::Pawn, false)?;
 
-        let x_start = SPACE_WIDTH * 8 + SPACE_WIDTH / 2;
+        let x_start = BOARD_X + SPACE_WIDTH * 8 + SPACE_WIDTH / 2;
         let y_start = SPACE_HEIGHT * 8 - 1;
 
         queue!(
             self.stdout,
             cursor::MoveTo(x_start, y_start),
-            style::SetForegroundColor(TermColor::Red),
+            style::SetForegroundColor(bottom_color),
         )?;
 
         // blank rows
@@ -429,11 +923,79 @@ impl Game {
         }
         queue!(self.stdout, cursor::MoveTo(x_start, y_start))?;
 
-        self.queue_captured_row(&white, PieceType::Pawn, true)?;
-        self.queue_captured_row(&white, PieceType::Knight, true)?;
-        self.queue_captured_row(&white, PieceType::Bishop, true)?;
-        self.queue_captured_row(&white, PieceType::Rook, true)?;
-        self.queue_captured_row(&white, PieceType::Queen, true)?;
+        self.queue_captured_row(&bottom, PieceType::Pawn, true)?;
+        self.queue_captured_row(&bottom, PieceType::Knight, true)?;
+        self.queue_captured_row(&bottom, PieceType::Bishop, true)?;
+        self.queue_captured_row(&bottom, PieceType::Rook, true)?;
+        self.queue_captured_row(&bottom, PieceType::Queen, true)?;
+
+        queue!(self.stdout, cursor::MoveTo(pos.0, pos.1), style::ResetColor)?;
+
+        Ok(())
+    }
+
+    // numbered SAN move list beside the captured pieces, including moves that can be redone
+    fn queue_move_history(&mut self) -> Result<()> {
+        let pos = cursor::position()?;
+
+        let mut line = self.board.clone();
+        while line.redo_move() {}
+        let sans = line.san_moves();
+        let start = self.board.starting_board();
+        let current = self.board.moves().len();
+        // when black moves first, the first row's white move is left empty
+        let offset = usize::from(start.turn_color() == Color::Black);
+        let first_number = usize::from(start.fullmove_number());
+        let rows = (sans.len() + offset).div_ceil(2);
+
+        // follow the current ply unless the list was scrolled since the last move
+        if current != self.history_ply {
+            let current_row = (current + offset).saturating_sub(1) / 2;
+            self.history_top = current_row.saturating_sub(HISTORY_ROWS - 1);
+            self.history_ply = current;
+        }
+        self.history_top = self.history_top.min(rows.saturating_sub(HISTORY_ROWS));
+
+        for i in 0..HISTORY_ROWS {
+            let row = self.history_top + i;
+            #[allow(clippy::cast_possible_truncation)]
+            queue!(
+                self.stdout,
+                cursor::MoveTo(HISTORY_X, 1 + i as u16),
+                style::SetBackgroundColor(TermColor::Black),
+                style::SetForegroundColor(TermColor::White),
+            )?;
+            if row >= rows {
+                queue!(
+                    self.stdout,
+                    style::Print(" ".repeat(5 + HISTORY_SAN_WIDTH * 2))
+                )?;
+                continue;
+            }
+            queue!(
+                self.stdout,
+                style::Print(format!("{:>3}. ", first_number + row))
+            )?;
+            for side in 0..2 {
+                let ply = (row * 2 + side).checked_sub(offset);
+                let (san, color) = match ply.and_then(|ply| sans.get(ply).map(|san| (ply, san))) {
+                    Some((ply, san)) if ply >= current => (san.as_str(), TermColor::DarkGrey),
+                    Some((_, san)) => (san.as_str(), TermColor::White),
+                    None if ply.is_none() => ("...", TermColor::White),
+                    None => ("", TermColor::White),
+                };
+                let content = if ply.is_some_and(|ply| ply + 1 == current) {
+                    san.with(TermColor::Black).on_grey()
+                } else {
+                    san.with(color).on_black()
+                };
+                queue!(
+                    self.stdout,
+                    style::PrintStyledContent(content),
+                    style::Print(" ".repeat(HISTORY_SAN_WIDTH.saturating_sub(san.len())))
+                )?;
+            }
+        }
 
         queue!(self.stdout, cursor::MoveTo(pos.0, pos.1), style::ResetColor)?;
 
@@ -486,3 +1048,35 @@ fn get_term_colors(space: &Space) -> (TermColor, TermColor) {
     };
     (piece_color, space_color)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uci_client::stand_in::{spawn, stand_in_engine};
+
+    #[test]
+    fn engine_without_a_move_gives_the_side_back() {
+        let (script, _) = stand_in_engine("game-none", "(none)");
+        let mut game = Game::new();
+        game.set_engine(Color::White);
+        game.set_uci_engine(spawn(&script));
+        assert!(game.is_engine_turn());
+        assert!(game.engine_move().is_err());
+        assert!(!game.is_engine_turn());
+    }
+
+    #[test]
+    fn selecting_another_own_piece_switches_to_it() {
+        let mut game = Game::new();
+        game.selected = Some((6, 0));
+        assert_eq!(game.activation(1, 0), Activation::Select);
+        assert_eq!(game.activation(6, 0), Activation::Deselect);
+        assert_eq!(
+            game.activation(5, 2),
+            Activation::Move(Move::new(6, 0, 5, 2, None))
+        );
+        game.selected = None;
+        assert_eq!(game.activation(4, 6), Activation::Nothing);
+        assert_eq!(game.activation(1, 0), Activation::Select);
+    }
+}
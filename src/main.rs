@@ -7,19 +7,161 @@
 )]
 
 mod board;
+mod chess_move;
 mod color;
+mod engine;
 mod game;
+mod game_result;
 mod move_record;
+mod pgn;
 mod piece;
 mod space;
+mod uci;
+mod uci_client;
+mod xboard;
+mod zobrist;
 
+use board::Board;
+use color::Color;
+use engine::SearchLimits;
 use game::Game;
-use std::io::Result;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Result};
+use std::process;
+use std::time::{Duration, Instant};
+use uci_client::UciEngine;
+
+const USAGE: &str = "usage: chess --uci | --xboard
+       chess perft <fen> <depth>
+       chess [--engine <white|black>] [--uci-engine <program>] [--depth <n> | --movetime <ms>]
+             [<file.pgn> [<game number>]]";
 
 fn main() -> Result<()> {
-    let mut game = Game::new();
+    match env::args().nth(1).as_deref() {
+        Some("--uci" | "--xboard") if env::args().len() > 2 => usage(),
+        Some("--uci") => return uci::run(io::stdin().lock(), io::stdout()),
+        Some("--xboard") => return xboard::run(io::stdin().lock(), io::stdout()),
+        Some("perft") => {
+            perft(&env::args().skip(2).collect::<Vec<_>>());
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let mut args = vec![];
+    let mut engine_color = None;
+    let mut uci_engine = None;
+    let mut limits = SearchLimits::default();
+    let mut options = env::args().skip(1);
+    while let Some(arg) = options.next() {
+        let value = match arg.as_str() {
+            "--engine" | "--uci-engine" | "--depth" | "--movetime" => {
+                options.next().unwrap_or_else(|| usage())
+            }
+            _ => {
+                args.push(arg);
+                continue;
+            }
+        };
+        match arg.as_str() {
+            "--engine" => {
+                engine_color = Some(match value.as_str() {
+                    "white" => Color::White,
+                    "black" => Color::Black,
+                    _ => usage(),
+                });
+            }
+            "--uci-engine" => uci_engine = Some(value),
+            "--depth" => limits.depth = Some(value.parse().unwrap_or_else(|_| usage())),
+            _ => {
+                let millis = value.parse().unwrap_or_else(|_| usage());
+                limits.movetime = Some(Duration::from_millis(millis));
+            }
+        }
+    }
+    // think for a second unless told otherwise
+    if limits == SearchLimits::default() {
+        limits.movetime = Some(Duration::from_secs(1));
+    }
+
+    let mut game = match args.as_slice() {
+        [] => Game::new(),
+        [path] | [path, _] => {
+            let Ok(number) = args.get(1).map_or(Ok(1), |n| n.parse::<usize>()) else {
+                usage();
+            };
+            match load_pgn(path, number) {
+                Ok(board) => Game::with_board(board),
+                Err(e) => {
+                    eprintln!("{path}: {e}");
+                    process::exit(1);
+                }
+            }
+        }
+        _ => usage(),
+    };
+    if let Some(program) = uci_engine {
+        match UciEngine::spawn(&program, &[]) {
+            Ok(uci_engine) => game.set_uci_engine(uci_engine),
+            Err(e) => {
+                eprintln!("{program}: {e}");
+                process::exit(1);
+            }
+        }
+    }
+    if let Some(color) = engine_color {
+        game.set_engine(color);
+    }
+    game.set_limits(limits);
 
     game.run_loop()?;
 
     Ok(())
 }
+
+fn load_pgn(path: &str, number: usize) -> std::result::Result<Board, Box<dyn Error>> {
+    let text = fs::read_to_string(path)?;
+    let games = pgn::parse_pgn(&text)?;
+    let game = games
+        .iter()
+        .find(|game| game.number() == number)
+        .ok_or_else(|| format!("no game {number}, file has {} games", games.len()))?;
+    Ok(game.replay()?)
+}
+
+// prints the node count below each legal move and the total, with the fen quoted or not
+fn perft(args: &[String]) {
+    let [fen @ .., depth] = args else {
+        usage();
+    };
+    let Ok(depth) = depth.parse() else {
+        usage();
+    };
+    let board = match Board::from_fen(&fen.join(" ")) {
+        Ok(board) => board,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+    let start = Instant::now();
+    let divide = board.divide(depth);
+    for (mv, nodes) in &divide {
+        println!("{mv}: {nodes}");
+    }
+    let nodes = if depth == 0 {
+        1
+    } else {
+        divide.iter().map(|&(_, nodes)| nodes).sum()
+    };
+    println!();
+    println!("Nodes searched: {nodes}");
+    println!("Time: {} ms", start.elapsed().as_millis());
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2);
+}
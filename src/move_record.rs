@@ -1,3 +1,4 @@
+use crate::chess_move::Move;
 use crate::piece::{Piece, PieceType};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -6,8 +7,10 @@ pub struct MoveRecord {
     dest: (u8, u8),
     capture: Option<Piece>,
     piece_type: PieceType,
-    first_move: bool, // true if this was the piece's first move
-    promotion: bool,
+    first_move: bool,             // true if this was the piece's first move
+    promotion: Option<PieceType>, // the piece a pawn promoted to
+    en_passant: bool,
+    halfmove_clock: u16, // the board's halfmove clock before this move
 }
 
 impl MoveRecord {
@@ -20,7 +23,9 @@ impl MoveRecord {
         capture: Option<Piece>,
         piece_type: PieceType,
         first_move: bool,
-        promotion: bool,
+        promotion: Option<PieceType>,
+        en_passant: bool,
+        halfmove_clock: u16,
     ) -> Self {
         Self {
             origin: (x1, y1),
@@ -29,6 +34,8 @@ impl MoveRecord {
             piece_type,
             first_move,
             promotion,
+            en_passant,
+            halfmove_clock,
         }
     }
 
@@ -56,7 +63,22 @@ impl MoveRecord {
         self.first_move
     }
 
-    pub fn promotion(&self) -> bool {
+    pub fn promotion(&self) -> Option<PieceType> {
         self.promotion
     }
+
+    pub fn en_passant(&self) -> bool {
+        self.en_passant
+    }
+
+    pub fn halfmove_clock(&self) -> u16 {
+        self.halfmove_clock
+    }
+
+    // the move that was made, as it would be passed to Board::make_move
+    pub fn as_move(&self) -> Move {
+        let (x1, y1) = self.origin;
+        let (x2, y2) = self.dest;
+        Move::new(x1, y1, x2, y2, self.promotion)
+    }
 }
@@ -0,0 +1,519 @@
+use crate::board::Board;
+use crate::board::fen::{FenError, STARTING_FEN};
+use crate::board::san::SanError;
+use crate::color::Color;
+use crate::game_result::GameResult;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAX_LINE_LENGTH: usize = 80;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnTags {
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+}
+
+impl Default for PgnTags {
+    fn default() -> Self {
+        Self {
+            event: "?".to_string(),
+            site: "?".to_string(),
+            date: "????.??.??".to_string(),
+            round: "?".to_string(),
+            white: "?".to_string(),
+            black: "?".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnGame {
+    number: usize, // 1-based position of the game in its file
+    tags: Vec<(String, String)>,
+    moves: Vec<String>,
+    result: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PgnError {
+    Syntax {
+        game: usize,
+        message: String,
+    },
+    InvalidFen {
+        game: usize,
+        error: FenError,
+    },
+    IllegalMove {
+        game: usize,
+        ply: usize,
+        error: SanError,
+    },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::Syntax { game, message } => write!(f, "game {game}: {message}"),
+            PgnError::InvalidFen { game, error } => write!(f, "game {game}: {error}"),
+            PgnError::IllegalMove { game, ply, error } => {
+                write!(f, "game {game}, ply {ply}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl PgnGame {
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn moves(&self) -> &[String] {
+        &self.moves
+    }
+
+    pub fn result(&self) -> Option<&str> {
+        self.result.as_deref()
+    }
+
+    // plays every move of the main line onto a new board, starting from the FEN tag if there is one
+    pub fn replay(&self) -> Result<Board, PgnError> {
+        let mut board = match self.tag("FEN") {
+            Some(fen) => Board::from_fen(fen).map_err(|error| PgnError::InvalidFen {
+                game: self.number,
+                error,
+            })?,
+            None => Board::new(),
+        };
+        for (i, san) in self.moves.iter().enumerate() {
+            let mv = board
+                .parse_san(san)
+                .map_err(|error| PgnError::IllegalMove {
+                    game: self.number,
+                    ply: i + 1,
+                    error,
+                })?;
+            board
+                .make_move(mv)
+                .expect("parse_san only returns legal moves");
+        }
+        Ok(board)
+    }
+}
+
+// reads every game in a PGN file, keeping only the main line of each
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut games = vec![];
+    let mut game = PgnGame {
+        number: 1,
+        tags: vec![],
+        moves: vec![],
+        result: None,
+    };
+    let mut variation_depth = 0;
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        let syntax = |game: &PgnGame, message: &str| PgnError::Syntax {
+            game: game.number,
+            message: message.to_string(),
+        };
+        let at_line_start = line_start;
+        line_start = c == '\n';
+        match c {
+            // lines starting with % are an escape mechanism for other tools
+            '%' if at_line_start => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line_start = true;
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line_start = true;
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                if !chars.by_ref().any(|c| c == '}') {
+                    return Err(syntax(&game, "unterminated comment"));
+                }
+            }
+            '(' => variation_depth += 1,
+            ')' => {
+                if variation_depth == 0 {
+                    return Err(syntax(&game, "unmatched ')'"));
+                }
+                variation_depth -= 1;
+            }
+            '[' if variation_depth == 0 => {
+                // a tag after movetext without a result starts the next game
+                if !game.moves.is_empty() {
+                    let number = game.number + 1;
+                    games.push(std::mem::replace(
+                        &mut game,
+                        PgnGame {
+                            number,
+                            tags: vec![],
+                            moves: vec![],
+                            result: None,
+                        },
+                    ));
+                }
+                let tag = parse_tag(&mut chars).ok_or_else(|| syntax(&game, "invalid tag"))?;
+                game.tags.push(tag);
+            }
+            c if c.is_whitespace() => {}
+            _ => {
+                let mut token = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "{}()[];".contains(c) {
+                        break;
+                    }
+                    token.push(c);
+                    chars.next();
+                }
+                if variation_depth > 0 || token.starts_with('$') {
+                    continue;
+                }
+                if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+                    game.result = Some(token);
+                    let number = game.number + 1;
+                    games.push(std::mem::replace(
+                        &mut game,
+                        PgnGame {
+                            number,
+                            tags: vec![],
+                            moves: vec![],
+                            result: None,
+                        },
+                    ));
+                    continue;
+                }
+                // move numbers may be attached to the move that follows them, as in 1.e4 or
+                // 5...O-O, but zero-style castling like 0-0 is a move on its own
+                let san = match token.rfind('.') {
+                    Some(i) if is_move_number(&token[..i]) => &token[i + 1..],
+                    _ => token.as_str(),
+                };
+                if !san.is_empty() {
+                    game.moves.push(san.to_string());
+                }
+            }
+        }
+    }
+    if variation_depth > 0 {
+        return Err(PgnError::Syntax {
+            game: game.number,
+            message: "unterminated variation".to_string(),
+        });
+    }
+    if !game.tags.is_empty() || !game.moves.is_empty() {
+        games.push(game);
+    }
+    Ok(games)
+}
+
+// digits followed by any number of dots, as in the 12 of 12. or the 12.. of 12...
+fn is_move_number(prefix: &str) -> bool {
+    let digits = prefix.trim_end_matches('.');
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+// parses the rest of a tag pair after its opening '['
+fn parse_tag(chars: &mut impl Iterator<Item = char>) -> Option<(String, String)> {
+    let mut name = String::new();
+    let mut chars = chars.skip_while(|c| c.is_whitespace());
+    loop {
+        match chars.next()? {
+            '"' => break,
+            c if c.is_whitespace() => {}
+            c => name.push(c),
+        }
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next()? {
+            '"' => break,
+            '\\' => value.push(chars.next()?),
+            c => value.push(c),
+        }
+    }
+    chars.find(|&c| c == ']')?;
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, value))
+}
+
+pub fn export_pgn(board: &Board, tags: &PgnTags, result: Option<GameResult>) -> String {
+    let result = result_token(result);
+    let mut pgn = String::new();
+    for (name, value) in [
+        ("Event", tags.event.as_str()),
+        ("Site", &tags.site),
+        ("Date", &tags.date),
+        ("Round", &tags.round),
+        ("White", &tags.white),
+        ("Black", &tags.black),
+        ("Result", result),
+    ] {
+        pgn.push_str(&tag_line(name, value));
+    }
+    let start = board.starting_board();
+    let fen = start.to_fen();
+    if fen != STARTING_FEN {
+        pgn.push_str(&tag_line("SetUp", "1"));
+        pgn.push_str(&tag_line("FEN", &fen));
+    }
+    pgn.push('\n');
+
+    let mut tokens = vec![];
+    let mut fullmove = start.fullmove_number();
+    let mut color = start.turn_color();
+    for (i, san) in board.san_moves().into_iter().enumerate() {
+        match color {
+            Color::White => {
+                tokens.push(format!("{fullmove}."));
+                color = Color::Black;
+            }
+            Color::Black => {
+                if i == 0 {
+                    tokens.push(format!("{fullmove}..."));
+                }
+                fullmove += 1;
+                color = Color::White;
+            }
+        }
+        tokens.push(san);
+    }
+    tokens.push(result.to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+    pgn
+}
+
+pub fn result_token(result: Option<GameResult>) -> &'static str {
+    match result {
+        Some(GameResult::WhiteWins) => "1-0",
+        Some(GameResult::BlackWins) => "0-1",
+        Some(GameResult::Draw(_)) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+fn tag_line(name: &str, value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{name} \"{value}\"]\n")
+}
+
+// today's UTC date as (year, month, day)
+pub fn today() -> (i64, u32, u32) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    #[allow(clippy::cast_possible_wrap)]
+    civil_from_days((secs / 86400) as i64)
+}
+
+// converts days since 1970-01-01 to a proleptic Gregorian date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_result::DrawReason;
+
+    #[test]
+    fn export_finished_game() {
+        let mut b = Board::new();
+        b.play_san(&["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]);
+        let tags = PgnTags {
+            event: "Club \"Blitz\"".to_string(),
+            date: "2024.01.02".to_string(),
+            ..PgnTags::default()
+        };
+        assert_eq!(
+            export_pgn(&b, &tags, b.result()),
+            "[Event \"Club \\\"Blitz\\\"\"]\n\
+             [Site \"?\"]\n\
+             [Date \"2024.01.02\"]\n\
+             [Round \"?\"]\n\
+             [White \"?\"]\n\
+             [Black \"?\"]\n\
+             [Result \"1-0\"]\n\
+             \n\
+             1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"
+        );
+    }
+
+    #[test]
+    fn export_from_fen_with_black_to_move() {
+        let mut b = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 12").unwrap();
+        b.play_san(&["Kd7", "e4"]);
+        let pgn = export_pgn(
+            &b,
+            &PgnTags::default(),
+            Some(GameResult::Draw(DrawReason::InsufficientMaterial)),
+        );
+        assert!(pgn.contains(
+            "[Result \"1/2-1/2\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n"
+        ));
+        assert!(pgn.ends_with("\n12... Kd7 13. e4 1/2-1/2\n"));
+    }
+
+    #[test]
+    fn long_movetext_is_wrapped() {
+        let mut b = Board::new();
+        for _ in 0..10 {
+            b.play_san(&["Nf3", "Nf6", "Ng1", "Ng8"]);
+        }
+        let pgn = export_pgn(&b, &PgnTags::default(), None);
+        let movetext = pgn.split("\n\n").nth(1).unwrap();
+        assert!(movetext.lines().count() > 1);
+        assert!(movetext.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert!(movetext.ends_with(" *\n"));
+    }
+
+    #[test]
+    fn import_multiple_games() {
+        let text = "[Event \"First \\\"game\\\"\"]\n\
+                    [White \"A\"]\n\
+                    \n\
+                    1. e4 {best by test} e5 $1 2.Nf3 (2. f4 exf4 (2... d5) 3. Nf3) 2... Nc6!? ; comment\n\
+                    % escaped line\n\
+                    3. Bb5 1-0\n\
+                    \n\
+                    [Event \"Second\"]\n\
+                    [SetUp \"1\"]\n\
+                    [FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n\
+                    \n\
+                    12... Kd7 13. e4 *\n";
+        let games = parse_pgn(text).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("Event"), Some("First \"game\""));
+        assert_eq!(games[0].tag("White"), Some("A"));
+        assert_eq!(games[0].moves(), ["e4", "e5", "Nf3", "Nc6!?", "Bb5"]);
+        assert_eq!(games[0].result(), Some("1-0"));
+        let b = games[0].replay().unwrap();
+        assert_eq!(b.san_moves(), ["e4", "e5", "Nf3", "Nc6", "Bb5"]);
+
+        assert_eq!(games[1].number(), 2);
+        let b = games[1].replay().unwrap();
+        assert_eq!(b.to_fen(), "8/3k4/8/8/4P3/8/8/4K3 b - e3 0 13");
+    }
+
+    #[test]
+    fn import_zero_style_castling() {
+        let text = "1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. d3 d6 5.0-0 Qe7 6. Nc3 Be6 7. Be3 0-0-0 \
+                    8. a3 Kb8 0-1\n";
+        let games = parse_pgn(text).unwrap();
+        assert_eq!(games[0].moves()[8], "0-0");
+        assert_eq!(games[0].moves()[13], "0-0-0");
+        let b = games[0].replay().unwrap();
+        assert_eq!(b.san_moves()[8], "O-O");
+        assert_eq!(b.san_moves()[13], "O-O-O");
+        assert_eq!(b.san_moves()[15], "Kb8");
+    }
+
+    #[test]
+    fn export_then_import() {
+        let mut b = Board::new();
+        for _ in 0..6 {
+            b.play_san(&["Nf3", "Nf6", "Ng1", "Ng8"]);
+        }
+        b.play_san(&["e4", "d5", "exd5", "Qxd5"]);
+        let pgn = export_pgn(&b, &PgnTags::default(), None);
+        let games = parse_pgn(&pgn).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].replay().unwrap(), b);
+    }
+
+    #[test]
+    fn import_errors() {
+        let games = parse_pgn("1. e4 e5 *\n\n1. e4 e5 2. Ke3 *").unwrap();
+        assert!(games[0].replay().is_ok());
+        assert_eq!(
+            games[1].replay(),
+            Err(PgnError::IllegalMove {
+                game: 2,
+                ply: 3,
+                error: SanError::IllegalMove("Ke3".to_string())
+            })
+        );
+        assert_eq!(
+            parse_pgn("1. e4 {unfinished"),
+            Err(PgnError::Syntax {
+                game: 1,
+                message: "unterminated comment".to_string()
+            })
+        );
+        assert_eq!(
+            parse_pgn("1. e4 e5 *\n1. d4 (1. c4"),
+            Err(PgnError::Syntax {
+                game: 2,
+                message: "unterminated variation".to_string()
+            })
+        );
+        assert!(matches!(
+            parse_pgn("[FEN \"bad\"]\n*").unwrap()[0].replay(),
+            Err(PgnError::InvalidFen { game: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+    }
+}
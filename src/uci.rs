@@ -0,0 +1,361 @@
+use crate::board::Board;
+use crate::chess_move::Move;
+use crate::color::Color;
+use crate::engine::{self, DEFAULT_MOVE_OVERHEAD, MAX_DEPTH, SearchInfo, SearchLimits};
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// speaks the Universal Chess Interface, reading commands from input until quit or end of input
+pub fn run<W: Write + Send + 'static>(input: impl BufRead, output: W) -> io::Result<()> {
+    let mut uci = Uci {
+        board: Board::new(),
+        output: Arc::new(Mutex::new(output)),
+        stop: Arc::new(AtomicBool::new(false)),
+        search: None,
+        infinite: false,
+        max_depth: MAX_DEPTH,
+        move_overhead: DEFAULT_MOVE_OVERHEAD,
+    };
+    let mut quit = false;
+    for line in input.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["uci"] => uci.identify()?,
+            ["isready"] => uci.send("readyok")?,
+            ["ucinewgame"] => {
+                uci.stop_search();
+                uci.board = Board::new();
+            }
+            ["position", rest @ ..] => {
+                uci.stop_search();
+                if let Err(e) = uci.set_position(rest) {
+                    uci.send(&format!("info string {e}"))?;
+                }
+            }
+            ["go", rest @ ..] => {
+                uci.stop_search();
+                uci.go(rest);
+            }
+            ["stop"] => uci.stop_search(),
+            ["setoption", rest @ ..] => {
+                if let Err(e) = uci.set_option(rest) {
+                    uci.send(&format!("info string {e}"))?;
+                }
+            }
+            ["quit"] => {
+                quit = true;
+                break;
+            }
+            [] => {}
+            _ => uci.send(&format!("info string unknown command: {line}"))?,
+        }
+    }
+    // without a quit, a search already started is allowed to finish
+    if quit || uci.infinite {
+        uci.stop_search();
+    } else if let Some(search) = uci.search.take() {
+        search.join().expect("search thread panicked");
+    }
+    Ok(())
+}
+
+struct Uci<W> {
+    board: Board,
+    output: Arc<Mutex<W>>,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+    infinite: bool, // the running search waits for stop before sending bestmove
+    max_depth: u8,
+    move_overhead: u64, // milliseconds kept back from the clock for communication delays
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    fn send(&self, line: &str) -> io::Result<()> {
+        send(&self.output, line)
+    }
+
+    fn identify(&self) -> io::Result<()> {
+        self.send(concat!("id name chess ", env!("CARGO_PKG_VERSION")))?;
+        self.send("id author the chess authors")?;
+        self.send(&format!(
+            "option name MaxDepth type spin default {MAX_DEPTH} min 1 max {MAX_DEPTH}"
+        ))?;
+        self.send(&format!(
+            "option name Move Overhead type spin default {DEFAULT_MOVE_OVERHEAD} min 0 max 5000"
+        ))?;
+        self.send("uciok")
+    }
+
+    // position [startpos | fen <fields>] [moves <move>...]
+    fn set_position(&mut self, args: &[&str]) -> Result<(), String> {
+        let moves_at = args
+            .iter()
+            .position(|&arg| arg == "moves")
+            .unwrap_or(args.len());
+        let mut board = match &args[..moves_at] {
+            ["startpos"] => Board::new(),
+            ["fen", fields @ ..] => {
+                Board::from_fen(&fields.join(" ")).map_err(|e| format!("invalid fen: {e}"))?
+            }
+            _ => return Err("expected startpos or fen".to_string()),
+        };
+        for &text in args.iter().skip(moves_at + 1) {
+            let mv = Move::from_coordinates(text).ok_or(format!("invalid move: {text}"))?;
+            board
+                .make_move(mv)
+                .map_err(|e| format!("illegal move {text}: {e}"))?;
+        }
+        self.board = board;
+        Ok(())
+    }
+
+    // setoption name <id> [value <x>]
+    fn set_option(&mut self, args: &[&str]) -> Result<(), String> {
+        let value_at = args
+            .iter()
+            .position(|&arg| arg == "value")
+            .unwrap_or(args.len());
+        let name = match &args[..value_at] {
+            ["name", name @ ..] => name.join(" "),
+            _ => return Err("expected setoption name <id> value <x>".to_string()),
+        };
+        let value = args.get(value_at + 1..).unwrap_or_default().join(" ");
+        match name.to_lowercase().as_str() {
+            "maxdepth" => {
+                self.max_depth = value
+                    .parse::<u8>()
+                    .map_err(|_| format!("invalid MaxDepth: {value}"))?
+                    .clamp(1, MAX_DEPTH);
+            }
+            "move overhead" => {
+                self.move_overhead = value
+                    .parse()
+                    .map_err(|_| format!("invalid Move Overhead: {value}"))?;
+            }
+            _ => return Err(format!("unknown option: {name}")),
+        }
+        Ok(())
+    }
+
+    // go [depth <n>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [movestogo <n>] [infinite]
+    fn go(&mut self, args: &[&str]) {
+        let value = |name: &str| {
+            args.iter()
+                .position(|&arg| arg == name)
+                .and_then(|i| args.get(i + 1))
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+        let infinite = args.contains(&"infinite");
+        self.infinite = infinite;
+        let mut limits = SearchLimits {
+            depth: value("depth").map(|depth| u8::try_from(depth).unwrap_or(MAX_DEPTH)),
+            movetime: value("movetime").map(Duration::from_millis),
+        };
+        let (time, increment) = match self.board.turn_color() {
+            Color::White => (value("wtime"), value("winc")),
+            Color::Black => (value("btime"), value("binc")),
+        };
+        if limits.movetime.is_none()
+            && let Some(time) = time
+        {
+            limits.movetime = SearchLimits::from_clock(
+                time,
+                increment.unwrap_or(0),
+                value("movestogo"),
+                self.move_overhead,
+            )
+            .movetime;
+        }
+        limits.depth = Some(limits.depth.unwrap_or(MAX_DEPTH).min(self.max_depth));
+
+        self.stop.store(false, Ordering::Relaxed);
+        let board = self.board.clone();
+        let output = Arc::clone(&self.output);
+        let stop = Arc::clone(&self.stop);
+        self.search = Some(thread::spawn(move || {
+            let result = engine::search(&board, limits, &stop, |info| {
+                // a closed output just means nobody is listening any more
+                let _ = send(&output, &info_line(info));
+            });
+            // infinite searches only report their move once told to stop
+            while infinite && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            let best = result
+                .and_then(|info| info.best_move())
+                .map_or("0000".to_string(), |mv| mv.to_string());
+            let _ = send(&output, &format!("bestmove {best}"));
+        }));
+    }
+
+    // ends the running search, which still sends its bestmove
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            search.join().expect("search thread panicked");
+        }
+    }
+}
+
+fn send<W: Write>(output: &Mutex<W>, line: &str) -> io::Result<()> {
+    let mut output = output.lock().unwrap();
+    writeln!(output, "{line}")?;
+    output.flush()
+}
+
+fn info_line(info: &SearchInfo) -> String {
+    let score = match info.mate_in() {
+        Some(moves) => format!("mate {moves}"),
+        None => format!("cp {}", info.score),
+    };
+    let millis = info.elapsed.as_millis();
+    let nps = u128::from(info.nodes) * 1000 / millis.max(1);
+    let pv: Vec<String> = info.pv.iter().map(Move::to_string).collect();
+    format!(
+        "info depth {} score {score} nodes {} nps {nps} time {millis} pv {}",
+        info.depth,
+        info.nodes,
+        pv.join(" ")
+    )
+}
+
+// feeds a protocol loop commands and reads back what it sent, for the uci and xboard tests
+#[cfg(test)]
+pub mod transcript {
+    use std::io::{self, Cursor, Write};
+    use std::sync::{Arc, Mutex};
+
+    // shared so a search thread can still write to it
+    #[derive(Clone, Default)]
+    pub struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    pub fn run_commands(
+        run: impl FnOnce(Cursor<String>, Output) -> io::Result<()>,
+        commands: &str,
+    ) -> Vec<String> {
+        let output = Output::default();
+        run(Cursor::new(commands.to_string()), output.clone()).unwrap();
+        let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        text.lines().map(str::to_string).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_commands(commands: &str) -> Vec<String> {
+        transcript::run_commands(run, commands)
+    }
+
+    fn best_move(lines: &[String]) -> &str {
+        lines
+            .iter()
+            .rev()
+            .find_map(|line| line.strip_prefix("bestmove "))
+            .unwrap()
+    }
+
+    #[test]
+    fn handshake() {
+        let lines = run_commands("uci\nisready\n");
+        assert!(lines[0].starts_with("id name "));
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("option name MaxDepth"))
+        );
+        assert_eq!(lines[lines.len() - 2], "uciok");
+        assert_eq!(lines[lines.len() - 1], "readyok");
+    }
+
+    #[test]
+    fn go_depth_from_startpos_with_moves() {
+        let lines = run_commands("position startpos moves e2e4 e7e5\ngo depth 2\nisready\n");
+        assert!(lines.iter().any(|line| line.starts_with("info depth 1 ")));
+        assert!(lines.iter().any(|line| line.starts_with("info depth 2 ")));
+        assert!(!lines.iter().any(|line| line.starts_with("info depth 3 ")));
+        let mut board = Board::new();
+        for mv in ["e2e4", "e7e5"] {
+            board
+                .make_move(Move::from_coordinates(mv).unwrap())
+                .unwrap();
+        }
+        let mv = Move::from_coordinates(best_move(&lines)).unwrap();
+        assert!(board.legal_moves().contains(&mv));
+    }
+
+    #[test]
+    fn finds_mate_from_fen() {
+        let lines = run_commands("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 3\n");
+        assert!(lines.iter().any(|line| line.contains("score mate 1")));
+        assert_eq!(best_move(&lines), "a1a8");
+    }
+
+    #[test]
+    fn promotion_moves() {
+        let lines = run_commands(
+            "position fen 4k3/P7/8/8/8/8/8/4K3 w - - 0 1 moves a7a8r e8d7\ngo depth 1\n",
+        );
+        assert!(!lines.iter().any(|line| line.starts_with("info string")));
+        assert!(!best_move(&lines).is_empty());
+    }
+
+    #[test]
+    fn movetime_and_clock() {
+        let lines = run_commands("go movetime 50\ngo wtime 1000 btime 1000 winc 10\n");
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.starts_with("bestmove"))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn stop_infinite_search() {
+        let lines = run_commands("go infinite\nstop\n");
+        assert!(lines.last().unwrap().starts_with("bestmove "));
+    }
+
+    #[test]
+    fn no_legal_moves() {
+        let lines = run_commands("position fen R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1\ngo depth 2\n");
+        assert_eq!(best_move(&lines), "0000");
+    }
+
+    #[test]
+    fn set_options() {
+        let lines = run_commands(
+            "setoption name MaxDepth value 1\nsetoption name Move Overhead value 10\ngo\nsetoption name Hash value 16\n",
+        );
+        assert!(lines.iter().any(|line| line.starts_with("info depth 1 ")));
+        assert!(!lines.iter().any(|line| line.starts_with("info depth 2 ")));
+        assert!(lines.contains(&"info string unknown option: Hash".to_string()));
+    }
+
+    #[test]
+    fn bad_positions() {
+        let lines = run_commands(
+            "position fen 8/8/8/8 w - - 0 1\nposition startpos moves e2e5\nposition startpos moves zz\n",
+        );
+        assert!(lines[0].starts_with("info string invalid fen"));
+        assert!(lines[1].starts_with("info string illegal move e2e5"));
+        assert_eq!(lines[2], "info string invalid move: zz");
+    }
+}
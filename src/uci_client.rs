@@ -0,0 +1,391 @@
+use crate::board::Board;
+use crate::board::fen::STARTING_FEN;
+use crate::chess_move::Move;
+use crate::engine::{MATE_SCORE, SearchInfo, SearchLimits};
+use crate::move_record::MoveRecord;
+use std::ffi::OsStr;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::time::{Duration, Instant};
+
+// an engine running as a child process that we talk to over UCI
+pub struct UciEngine {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    name: Option<String>,
+}
+
+impl UciEngine {
+    // starts the engine and waits for it to finish the uci handshake
+    pub fn spawn<S: AsRef<OsStr>>(program: S, args: &[S]) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut engine = Self {
+            child,
+            stdin,
+            stdout,
+            name: None,
+        };
+        engine.send("uci")?;
+        loop {
+            let line = engine.read_line()?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = Some(name.to_string());
+            } else if line == "uciok" {
+                break;
+            }
+        }
+        engine.wait_ready()?;
+        Ok(engine)
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.send(&format!("setoption name {name} value {value}"))?;
+        self.wait_ready()
+    }
+
+    pub fn new_game(&mut self) -> io::Result<()> {
+        self.send("ucinewgame")?;
+        self.wait_ready()
+    }
+
+    // sends the board's game and searches it, returning the engine's last reported line
+    // ending in its bestmove, or None if it found no move to play
+    pub fn search(
+        &mut self,
+        board: &Board,
+        limits: SearchLimits,
+    ) -> io::Result<Option<SearchInfo>> {
+        self.send(&position_command(board))?;
+        self.send(&go_command(limits))?;
+        let start = Instant::now();
+        let mut last_info: Option<SearchInfo> = None;
+        loop {
+            let line = self.read_line()?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["info", rest @ ..] => {
+                    if let Some(info) = parse_info(rest) {
+                        last_info = Some(info);
+                    }
+                }
+                ["bestmove", best, ..] => {
+                    let Some(best) = Move::from_coordinates(best) else {
+                        // engines say (none) or 0000 when there is nothing to play
+                        return Ok(None);
+                    };
+                    if board.clone().make_move(best).is_err() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("engine sent illegal move {best}"),
+                        ));
+                    }
+                    let mut info = last_info.unwrap_or(SearchInfo {
+                        depth: 0,
+                        score: 0,
+                        nodes: 0,
+                        elapsed: start.elapsed(),
+                        pv: vec![],
+                    });
+                    if info.best_move() != Some(best) {
+                        info.pv = vec![best];
+                    }
+                    return Ok(Some(info));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn best_move(&mut self, board: &Board, limits: SearchLimits) -> io::Result<Option<Move>> {
+        Ok(self
+            .search(board, limits)?
+            .and_then(|info| info.best_move()))
+    }
+
+    fn wait_ready(&mut self) -> io::Result<()> {
+        self.send("isready")?;
+        while self.read_line()? != "readyok" {}
+        Ok(())
+    }
+
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{command}")?;
+        self.stdin.flush()
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "engine closed its output",
+            ));
+        }
+        Ok(line.trim_end().to_string())
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        // give the engine a moment to quit on its own before killing it
+        let _ = self.send("quit");
+        for _ in 0..10 {
+            if !matches!(self.child.try_wait(), Ok(None)) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// the game so far, from its starting position so the engine knows about repetitions
+fn position_command(board: &Board) -> String {
+    let start = board.starting_board().to_fen();
+    let mut command = if start == STARTING_FEN {
+        "position startpos".to_string()
+    } else {
+        format!("position fen {start}")
+    };
+    if !board.moves().is_empty() {
+        let moves: Vec<String> = board
+            .moves()
+            .iter()
+            .map(|record| MoveRecord::as_move(record).to_string())
+            .collect();
+        command.push_str(" moves ");
+        command.push_str(&moves.join(" "));
+    }
+    command
+}
+
+fn go_command(limits: SearchLimits) -> String {
+    // an engine with no limits would search until told to stop
+    let limits = limits.or_default_movetime();
+    let mut command = vec!["go".to_string()];
+    if let Some(depth) = limits.depth {
+        command.push(format!("depth {depth}"));
+    }
+    if let Some(movetime) = limits.movetime {
+        command.push(format!("movetime {}", movetime.as_millis()));
+    }
+    command.join(" ")
+}
+
+// info depth <n> score cp <x> | mate <n> ... nodes <n> time <ms> pv <moves>, ignoring lines without a score
+fn parse_info(words: &[&str]) -> Option<SearchInfo> {
+    let value = |name: &str| {
+        words
+            .iter()
+            .position(|&word| word == name)
+            .and_then(|i| words.get(i + 1))
+    };
+    let score_at = words.iter().position(|&word| word == "score")?;
+    let score = match words.get(score_at + 1..score_at + 3)? {
+        ["cp", cp] => cp.parse().ok()?,
+        ["mate", moves] => {
+            let moves: i32 = moves.parse().ok()?;
+            if moves > 0 {
+                MATE_SCORE - (moves * 2 - 1)
+            } else {
+                -MATE_SCORE - moves * 2
+            }
+        }
+        _ => return None,
+    };
+    let pv = words
+        .iter()
+        .position(|&word| word == "pv")
+        .map(|i| {
+            words[i + 1..]
+                .iter()
+                .map_while(|word| Move::from_coordinates(word))
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(SearchInfo {
+        depth: value("depth")?.parse().ok()?,
+        score,
+        nodes: value("nodes").and_then(|n| n.parse().ok()).unwrap_or(0),
+        elapsed: Duration::from_millis(value("time").and_then(|t| t.parse().ok()).unwrap_or(0)),
+        pv,
+    })
+}
+
+// scripted engines for tests, so they don't depend on a real engine being installed
+#[cfg(test)]
+pub mod stand_in {
+    use super::UciEngine;
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    // a stand-in engine that logs what it is sent and always answers with the same move
+    pub fn stand_in_engine(name: &str, best: &str) -> (PathBuf, PathBuf) {
+        let dir = env::temp_dir().join(format!("chess-uci-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("engine.sh");
+        let log = dir.join("log");
+        fs::write(
+            &script,
+            format!(
+                r#"while read -r line; do
+    echo "$line" >> "{log}"
+    case "$line" in
+        uci) echo "id name Stand-in"; echo "option name Skill type spin default 1 min 0 max 20"; echo uciok ;;
+        isready) echo readyok ;;
+        go*) echo "info string thinking"; echo "info depth 1 score cp 20 pv {best}"; echo "info depth 2 score mate -3 nodes 99 time 5 pv {best} e7e5"; echo "bestmove {best} ponder e7e5" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#,
+                log = log.display()
+            ),
+        )
+        .unwrap();
+        (script, log)
+    }
+
+    pub fn spawn(script: &Path) -> UciEngine {
+        let script = script.to_str().unwrap();
+        UciEngine::spawn("sh", &[script]).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stand_in::{spawn, stand_in_engine};
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn handshake_and_options() {
+        let (script, log) = stand_in_engine("handshake", "e2e4");
+        let mut engine = spawn(&script);
+        assert_eq!(engine.name(), Some("Stand-in"));
+        engine.set_option("Skill", "5").unwrap();
+        engine.new_game().unwrap();
+        drop(engine);
+        let log = fs::read_to_string(log).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "uci",
+                "isready",
+                "setoption name Skill value 5",
+                "isready",
+                "ucinewgame",
+                "isready",
+                "quit"
+            ]
+        );
+    }
+
+    #[test]
+    fn sends_position_and_reads_bestmove() {
+        let (script, log) = stand_in_engine("bestmove", "g8f6");
+        let mut engine = spawn(&script);
+        let mut board = Board::new();
+        board
+            .make_move(Move::from_coordinates("e2e4").unwrap())
+            .unwrap();
+        let info = engine
+            .search(&board, SearchLimits::depth(2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.best_move(), Move::from_coordinates("g8f6"));
+        assert_eq!(info.depth, 2);
+        assert_eq!(info.mate_in(), Some(-3));
+        assert_eq!(info.nodes, 99);
+        assert_eq!(info.pv.len(), 2);
+        drop(engine);
+        let log = fs::read_to_string(log).unwrap();
+        assert!(log.contains("position startpos moves e2e4\ngo depth 2\n"));
+    }
+
+    #[test]
+    fn sends_fen_and_promotions() {
+        let (script, log) = stand_in_engine("fen", "e8d7");
+        let mut engine = spawn(&script);
+        let mut board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        board
+            .make_move(Move::from_coordinates("a7a8q").unwrap())
+            .unwrap();
+        let best = engine
+            .best_move(&board, SearchLimits::movetime(Duration::from_millis(300)))
+            .unwrap();
+        assert_eq!(best, Move::from_coordinates("e8d7"));
+        drop(engine);
+        let log = fs::read_to_string(log).unwrap();
+        assert!(log.contains(
+            "position fen 4k3/P7/8/8/8/8/8/4K3 w - - 0 1 moves a7a8q\ngo movetime 300\n"
+        ));
+    }
+
+    #[test]
+    fn no_move_to_play() {
+        let (script, _) = stand_in_engine("none", "(none)");
+        let mut engine = spawn(&script);
+        assert_eq!(
+            engine
+                .best_move(&Board::new(), SearchLimits::default())
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn illegal_bestmove() {
+        let (script, _) = stand_in_engine("illegal", "e7e5");
+        let mut engine = spawn(&script);
+        let error = engine
+            .best_move(&Board::new(), SearchLimits::default())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn missing_engine() {
+        assert!(UciEngine::spawn("/nonexistent/engine", &[]).is_err());
+    }
+
+    #[test]
+    fn parses_info() {
+        let words: Vec<&str> =
+            "depth 7 seldepth 9 score cp -35 nodes 1234 nps 1 time 42 pv e2e4 e7e5"
+                .split(' ')
+                .collect();
+        let info = parse_info(&words).unwrap();
+        assert_eq!(info.depth, 7);
+        assert_eq!(info.score, -35);
+        assert_eq!(info.elapsed, Duration::from_millis(42));
+        assert_eq!(info.pv.len(), 2);
+        let words = ["depth", "3", "score", "mate", "2", "pv", "a1a8"];
+        assert_eq!(parse_info(&words).unwrap().mate_in(), Some(2));
+        assert!(parse_info(&["string", "hello"]).is_none());
+    }
+
+    #[test]
+    fn go_commands() {
+        assert_eq!(go_command(SearchLimits::depth(5)), "go depth 5");
+        assert_eq!(
+            go_command(SearchLimits::movetime(Duration::from_millis(250))),
+            "go movetime 250"
+        );
+        assert_eq!(go_command(SearchLimits::default()), "go movetime 1000");
+    }
+}
@@ -0,0 +1,355 @@
+use crate::board::Board;
+use crate::chess_move::Move;
+use crate::color::Color;
+use crate::engine::{self, DEFAULT_MOVE_OVERHEAD, MAX_DEPTH, SearchInfo, SearchLimits};
+use crate::game_result::{DrawReason, GameResult};
+use crate::pgn;
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+// speaks the Chess Engine Communication Protocol used by XBoard and WinBoard,
+// reading commands from input until quit or end of input
+pub fn run(input: impl BufRead, output: impl Write) -> io::Result<()> {
+    let mut xboard = XBoard {
+        board: Board::new(),
+        output,
+        engine: Some(Color::Black),
+        depth: None,
+        movetime: None,
+        clock: None,
+        moves_per_session: 0,
+        increment: 0,
+        post: false,
+    };
+    for line in input.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            // nothing to do for these
+            ["xboard" | "random" | "hard" | "easy" | "computer" | "draw" | "hint" | "?"]
+            | ["accepted" | "rejected" | "otim", _]
+            | ["name", ..]
+            | [] => {}
+            ["protover", ..] => xboard.send_features()?,
+            ["new"] => {
+                xboard.board = Board::new();
+                xboard.engine = Some(Color::Black);
+                xboard.depth = None;
+            }
+            // stop playing, either to take moves for both sides or because the
+            // interface has decided the game is over
+            ["force"] | ["result", ..] => xboard.engine = None,
+            ["go"] => {
+                xboard.engine = Some(xboard.board.turn_color());
+                xboard.think()?;
+            }
+            ["usermove", text] => xboard.user_move(text)?,
+            ["undo"] => xboard.board.undo_last_move(),
+            // takes back a move for each side so the user can play again
+            ["remove"] => {
+                xboard.board.undo_last_move();
+                xboard.board.undo_last_move();
+            }
+            ["setboard", fen @ ..] => match Board::from_fen(&fen.join(" ")) {
+                Ok(board) => xboard.board = board,
+                Err(e) => xboard.send(&format!("tellusererror Illegal position: {e}"))?,
+            },
+            ["ping", n] => xboard.send(&format!("pong {n}"))?,
+            ["sd", depth] => match depth.parse::<u8>() {
+                Ok(depth) => xboard.depth = Some(depth.clamp(1, MAX_DEPTH)),
+                Err(_) => xboard.send(&format!("Error (invalid depth): {line}"))?,
+            },
+            ["st", seconds] => match seconds.parse() {
+                Ok(seconds) => xboard.movetime = Some(Duration::from_secs(seconds)),
+                Err(_) => xboard.send(&format!("Error (invalid time): {line}"))?,
+            },
+            ["level", moves, _, increment] => {
+                xboard.moves_per_session = moves.parse().unwrap_or(0);
+                xboard.increment = increment.parse().unwrap_or(0);
+                xboard.movetime = None;
+            }
+            ["time", centiseconds] => xboard.clock = centiseconds.parse().ok(),
+            ["post"] => xboard.post = true,
+            ["nopost"] => xboard.post = false,
+            ["quit"] => break,
+            // version 1 interfaces send moves without usermove
+            [text] if Move::from_coordinates(text).is_some() => xboard.user_move(text)?,
+            _ => xboard.send(&format!("Error (unknown command): {line}"))?,
+        }
+    }
+    Ok(())
+}
+
+struct XBoard<W> {
+    board: Board,
+    output: W,
+    engine: Option<Color>, // the side we play, none in force mode
+    depth: Option<u8>,
+    movetime: Option<Duration>,
+    clock: Option<u64>,     // our remaining time in centiseconds
+    moves_per_session: u64, // moves in each time control, 0 for the whole game
+    increment: u64,         // seconds added after each move
+    post: bool,             // send thinking output
+}
+
+impl<W: Write> XBoard<W> {
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.output, "{line}")?;
+        self.output.flush()
+    }
+
+    fn send_features(&mut self) -> io::Result<()> {
+        self.send(concat!(
+            "feature myname=\"chess ",
+            env!("CARGO_PKG_VERSION"),
+            "\" setboard=1 usermove=1 ping=1 playother=0 colors=0 sigint=0 sigterm=0 analyze=0 draw=0 name=0 done=1"
+        ))
+    }
+
+    fn user_move(&mut self, text: &str) -> io::Result<()> {
+        let Some(mv) = Move::from_coordinates(text).or_else(|| self.board.parse_san(text).ok())
+        else {
+            return self.send(&format!("Illegal move: {text}"));
+        };
+        if let Err(e) = self.board.make_move(mv) {
+            return self.send(&format!("Illegal move ({e}): {text}"));
+        }
+        if !self.send_result()? && self.engine == Some(self.board.turn_color()) {
+            self.think()?;
+        }
+        Ok(())
+    }
+
+    // searches and plays a move for the side to move
+    fn think(&mut self) -> io::Result<()> {
+        if self.send_result()? {
+            return Ok(());
+        }
+        let limits = self.limits();
+        let post = self.post;
+        let output = &mut self.output;
+        let info = engine::search(&self.board, limits, &AtomicBool::new(false), |info| {
+            if post {
+                // a failed write shows up again when the move is sent
+                let _ = writeln!(output, "{}", thinking_line(info)).and_then(|()| output.flush());
+            }
+        });
+        // stop playing rather than take the interface down with us
+        let Some(mv) = info.and_then(|info| info.best_move()) else {
+            self.engine = None;
+            return self.send("tellusererror Engine found no move");
+        };
+        if let Err(e) = self.board.make_move(mv) {
+            self.engine = None;
+            return self.send(&format!(
+                "tellusererror Engine chose an illegal move ({e}): {mv}"
+            ));
+        }
+        self.send(&format!("move {mv}"))?;
+        self.send_result()?;
+        Ok(())
+    }
+
+    fn limits(&self) -> SearchLimits {
+        let movetime = self.movetime.or_else(|| {
+            let clock = self.clock?;
+            // the moves left in this session, if the time control has sessions
+            let moves_played = self.board.moves().len() as u64 / 2;
+            let moves_to_go = (self.moves_per_session > 0)
+                .then(|| self.moves_per_session - moves_played % self.moves_per_session);
+            SearchLimits::from_clock(
+                clock * 10,
+                self.increment * 1000,
+                moves_to_go,
+                DEFAULT_MOVE_OVERHEAD,
+            )
+            .movetime
+        });
+        SearchLimits {
+            depth: self.depth,
+            movetime,
+        }
+        .or_default_movetime()
+    }
+
+    // announces the end of the game, returning whether it is over
+    fn send_result(&mut self) -> io::Result<bool> {
+        let Some(result) = self.board.result() else {
+            return Ok(false);
+        };
+        let comment = match result {
+            GameResult::WhiteWins => "White mates",
+            GameResult::BlackWins => "Black mates",
+            GameResult::Draw(DrawReason::Stalemate) => "Stalemate",
+            GameResult::Draw(DrawReason::FiftyMoveRule | DrawReason::SeventyFiveMoveRule) => {
+                "Draw by fifty-move rule"
+            }
+            GameResult::Draw(DrawReason::ThreefoldRepetition | DrawReason::FivefoldRepetition) => {
+                "Draw by repetition"
+            }
+            GameResult::Draw(DrawReason::InsufficientMaterial) => "Insufficient material",
+        };
+        self.send(&format!(
+            "{} {{{comment}}}",
+            pgn::result_token(Some(result))
+        ))?;
+        Ok(true)
+    }
+}
+
+// ply score time nodes pv, with the time in centiseconds and mates scored as 100000 + moves
+fn thinking_line(info: &SearchInfo) -> String {
+    let score = match info.mate_in() {
+        Some(moves) if moves > 0 => 100_000 + moves,
+        Some(moves) => -100_000 + moves,
+        None => info.score,
+    };
+    let pv: Vec<String> = info.pv.iter().map(Move::to_string).collect();
+    format!(
+        "{} {score} {} {} {}",
+        info.depth,
+        info.elapsed.as_millis() / 10,
+        info.nodes,
+        pv.join(" ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::DEFAULT_MOVETIME;
+    use crate::uci::transcript;
+
+    fn run_commands(commands: &str) -> Vec<String> {
+        transcript::run_commands(run, commands)
+    }
+
+    fn moves(lines: &[String]) -> Vec<&str> {
+        lines
+            .iter()
+            .filter_map(|line| line.strip_prefix("move "))
+            .collect()
+    }
+
+    #[test]
+    fn handshake() {
+        let lines = run_commands("xboard\nprotover 2\naccepted setboard\nping 7\n");
+        assert!(lines[0].starts_with("feature myname=\"chess "));
+        assert!(lines[0].contains("usermove=1"));
+        assert!(lines[0].contains("setboard=1"));
+        assert!(lines[0].contains("draw=0 name=0"));
+        assert!(lines[0].ends_with("done=1"));
+        assert_eq!(lines[1], "pong 7");
+    }
+
+    #[test]
+    fn ignores_offers_and_names() {
+        let lines = run_commands(
+            "new
+draw
+name Someone Else
+?
+hint
+ping 3
+",
+        );
+        assert_eq!(lines, ["pong 3"]);
+    }
+
+    #[test]
+    fn replies_to_user_moves() {
+        let lines = run_commands("xboard\nnew\nsd 2\nusermove e2e4\nping 1\n");
+        let replies = moves(&lines);
+        assert_eq!(replies.len(), 1);
+        let mut board = Board::new();
+        board
+            .make_move(Move::from_coordinates("e2e4").unwrap())
+            .unwrap();
+        let reply = Move::from_coordinates(replies[0]).unwrap();
+        assert!(board.legal_moves().contains(&reply));
+        assert_eq!(lines.last().unwrap(), "pong 1");
+    }
+
+    #[test]
+    fn force_and_go() {
+        // no replies in force mode, then we take over the side to move
+        let lines = run_commands("new\nforce\nusermove e2e4\nusermove e7e5\nsd 1\npost\ngo\n");
+        assert_eq!(moves(&lines).len(), 1);
+        assert!(lines[0].starts_with("1 "));
+        // and keep playing it
+        let lines = run_commands("new\nforce\nsd 1\ngo\nusermove e7e5\n");
+        assert_eq!(moves(&lines).len(), 2);
+    }
+
+    #[test]
+    fn undo_and_remove() {
+        let lines =
+            run_commands("new\nforce\nusermove e2e4\nusermove e7e5\nundo\nusermove e7e5\nping 1\n");
+        assert_eq!(lines, ["pong 1"]);
+        // remove takes back a move for each side
+        let lines = run_commands(
+            "new\nforce\nusermove e2e4\nusermove e7e5\nremove\nusermove e2e4\nusermove d7d5\nping 1\n",
+        );
+        assert_eq!(lines, ["pong 1"]);
+        let lines = run_commands("new\nforce\nundo\nusermove e2e5\n");
+        assert_eq!(lines, ["Illegal move (piece can't move that way): e2e5"]);
+    }
+
+    #[test]
+    fn setboard_and_mate() {
+        let lines = run_commands("force\nsetboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\nsd 3\ngo\n");
+        assert_eq!(lines, ["move a1a8", "1-0 {White mates}"]);
+        let lines = run_commands("setboard 8/8/8 w - - 0 1\n");
+        assert!(lines[0].starts_with("tellusererror Illegal position"));
+    }
+
+    #[test]
+    fn user_mates() {
+        let lines = run_commands(
+            "new\nsetboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\nusermove a1a8\nresult 1-0 {White mates}\n",
+        );
+        assert_eq!(lines, ["1-0 {White mates}"]);
+    }
+
+    #[test]
+    fn result_stops_play() {
+        let lines = run_commands("new\nresult 1/2-1/2 {agreed}\nusermove e2e4\nping 2\n");
+        assert_eq!(lines, ["pong 2"]);
+    }
+
+    #[test]
+    fn errors() {
+        let lines = run_commands("usermove zz\nfoo\nsd x\n");
+        assert_eq!(
+            lines,
+            [
+                "Illegal move: zz",
+                "Error (unknown command): foo",
+                "Error (invalid depth): sd x"
+            ]
+        );
+    }
+
+    #[test]
+    fn clock_limits() {
+        let lines = run_commands("new\nlevel 40 5 0\ntime 3000\notim 3000\nusermove e2e4\n");
+        assert_eq!(moves(&lines).len(), 1);
+        let mut xboard = XBoard {
+            board: Board::new(),
+            output: vec![],
+            engine: None,
+            depth: None,
+            movetime: None,
+            clock: Some(6000),
+            moves_per_session: 40,
+            increment: 2,
+            post: false,
+        };
+        assert_eq!(xboard.limits().movetime, Some(Duration::from_millis(2950)));
+        xboard.clock = None;
+        assert_eq!(xboard.limits().movetime, Some(DEFAULT_MOVETIME));
+        xboard.depth = Some(4);
+        assert_eq!(xboard.limits(), SearchLimits::depth(4));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::color::Color;
+use crate::piece::PieceType;
+
+const PIECE_KEYS: usize = 0;
+const BLACK_TO_MOVE_KEY: usize = PIECE_KEYS + 2 * 6 * 64;
+const CASTLING_KEYS: usize = BLACK_TO_MOVE_KEY + 1;
+const EN_PASSANT_KEYS: usize = CASTLING_KEYS + 4;
+const KEY_COUNT: usize = EN_PASSANT_KEYS + 8;
+
+const KEYS: [u64; KEY_COUNT] = generate_keys();
+
+// fixed splitmix64 sequence so hashes are stable between runs
+const fn generate_keys() -> [u64; KEY_COUNT] {
+    let mut keys = [0; KEY_COUNT];
+    let mut state: u64 = 0;
+    let mut i = 0;
+    while i < KEY_COUNT {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+pub fn piece_key(color: Color, piece_type: PieceType, x: u8, y: u8) -> u64 {
+    let color_index = match color {
+        Color::White => 0,
+        Color::Black => 1,
+    };
+    let type_index = match piece_type {
+        PieceType::King => 0,
+        PieceType::Queen => 1,
+        PieceType::Rook => 2,
+        PieceType::Bishop => 3,
+        PieceType::Knight => 4,
+        PieceType::Pawn => 5,
+    };
+    KEYS[PIECE_KEYS + (color_index * 6 + type_index) * 64 + usize::from(y) * 8 + usize::from(x)]
+}
+
+pub fn black_to_move_key() -> u64 {
+    KEYS[BLACK_TO_MOVE_KEY]
+}
+
+pub fn castling_key(color: Color, kingside: bool) -> u64 {
+    let index = match (color, kingside) {
+        (Color::White, true) => 0,
+        (Color::White, false) => 1,
+        (Color::Black, true) => 2,
+        (Color::Black, false) => 3,
+    };
+    KEYS[CASTLING_KEYS + index]
+}
+
+pub fn en_passant_key(file: u8) -> u64 {
+    KEYS[EN_PASSANT_KEYS + usize::from(file)]
+}